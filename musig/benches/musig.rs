@@ -0,0 +1,39 @@
+#[macro_use]
+extern crate criterion;
+use criterion::Criterion;
+
+use curve25519_dalek::scalar::Scalar;
+use rand::thread_rng;
+
+use musig::{Multikey, VerificationKey};
+
+fn aggregate_multikey_helper(n: usize, c: &mut Criterion) {
+    let label = format!("Multikey aggregation with {} keys", n);
+
+    c.bench_function(&label, move |b| {
+        let mut rng = thread_rng();
+        let pubkeys: Vec<VerificationKey> = (0..n)
+            .map(|_| VerificationKey::from_secret(&Scalar::random(&mut rng)))
+            .collect();
+        b.iter(|| {
+            Multikey::new(pubkeys.clone()).unwrap();
+        })
+    });
+}
+
+fn aggregate_multikey_16(c: &mut Criterion) {
+    aggregate_multikey_helper(16, c);
+}
+
+fn aggregate_multikey_256(c: &mut Criterion) {
+    aggregate_multikey_helper(256, c);
+}
+
+criterion_group! {
+    name = aggregate_multikey;
+    config = Criterion::default().sample_size(10);
+    targets = aggregate_multikey_16,
+        aggregate_multikey_256,
+}
+
+criterion_main!(aggregate_multikey);
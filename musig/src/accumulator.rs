@@ -0,0 +1,84 @@
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+use starsig::VerificationKey;
+use std::collections::BTreeSet;
+
+use super::{Multikey, MusigError};
+
+/// Incremental builder of a `Multikey` for very large sets of keys.
+/// Keys are validated (decompressed and checked for duplicates) as they arrive,
+/// so this work can be spread across poll iterations.
+/// The delinearization factors depend on the entire list of keys,
+/// therefore the keys are buffered and aggregated in `finalize`.
+#[derive(Clone, Default)]
+pub struct MultikeyAccumulator {
+    pubkeys: Vec<VerificationKey>,
+    points: Vec<RistrettoPoint>,
+    seen: BTreeSet<[u8; 32]>,
+}
+
+impl MultikeyAccumulator {
+    /// Creates an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the next key. Fails if the key is not a valid point
+    /// or if it was already added, with the same errors as `Multikey::new`.
+    pub fn add_key(&mut self, pubkey: VerificationKey) -> Result<(), MusigError> {
+        let point = Multikey::decompress_key(self.pubkeys.len(), &pubkey)?;
+        if !self.seen.insert(pubkey.to_bytes()) {
+            return Err(MusigError::DuplicateKey {
+                pubkey: pubkey.to_bytes(),
+            });
+        }
+        self.pubkeys.push(pubkey);
+        self.points.push(point);
+        Ok(())
+    }
+
+    /// Returns the number of keys added so far.
+    pub fn len(&self) -> usize {
+        self.pubkeys.len()
+    }
+
+    /// Returns true if no keys were added.
+    pub fn is_empty(&self) -> bool {
+        self.pubkeys.is_empty()
+    }
+
+    /// Computes the aggregated key of the keys added so far, without consuming the accumulator,
+    /// e.g. to display it while the keys are being entered.
+    /// Every factor `a_i` depends on the entire list, so adding a key changes all of them:
+    /// the preview recomputes the `n` factors, but reuses the already decompressed keys.
+    /// Fails with `MusigError::BadArguments` if no keys were added.
+    pub fn preview_aggregate(&self) -> Result<VerificationKey, MusigError> {
+        match self.pubkeys.len() {
+            0 => return Err(MusigError::BadArguments),
+            1 => {
+                // Single key is returned without a delinearization factor, like in `Multikey::new`.
+                // It was already validated by `add_key`.
+                return Ok(self.pubkeys[0]);
+            }
+            _ => {}
+        }
+        let prf = Multikey::transcript_for_keys(&self.pubkeys);
+        // aggregated_key = sum_i ( a_i * X_i )
+        let aggregated_key = RistrettoPoint::vartime_multiscalar_mul(
+            (0..self.pubkeys.len()).map(|i| Multikey::compute_factor(&prf, i)),
+            &self.points,
+        );
+        Ok(VerificationKey::from(aggregated_key))
+    }
+
+    /// Aggregates all the added keys into a `Multikey`,
+    /// identical to the one created with `Multikey::new` from the same list of keys.
+    pub fn finalize(self) -> Result<Multikey, MusigError> {
+        if self.pubkeys.len() < 2 {
+            // Empty list and a single key are handled without aggregation.
+            return Multikey::new(self.pubkeys);
+        }
+        let prf = Multikey::transcript_for_keys(&self.pubkeys);
+        Multikey::aggregate(prf, self.pubkeys, self.points, None)
+    }
+}
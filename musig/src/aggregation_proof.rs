@@ -0,0 +1,173 @@
+use merkle::{Hash, Hasher, MerkleItem, MerkleTree, Path};
+use merlin::Transcript;
+use readerwriter::{Decodable, Encodable, ExactSizeEncodable, Reader, WriteError, Writer};
+
+use starsig::VerificationKey;
+
+use super::serialization::read_key;
+use super::transcript::LABEL_INDEX;
+use super::{Multikey, MusigContext, MusigError};
+
+/// Proof that a key (with its weight, if the multikey is weighted) is at a given position
+/// of the ordered list of keys committed to by a merkle root (see `Multikey::key_list_commitment`).
+///
+/// Every coefficient `a_i = H(<L>, i)` depends on the whole list `<L>`, so the aggregated key
+/// can only be checked against the commitment with all the keys (see `Multikey::verify_key_list`).
+/// A light verifier that only has the commitment checks the membership of a single key
+/// with this proof instead, in time logarithmic in the number of keys.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AggregationProof {
+    leaf: KeyLeaf,
+    path: Path,
+}
+
+/// Key of the party at a given position, with its weight if the multikey is weighted.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct KeyLeaf {
+    position: usize,
+    key: VerificationKey,
+    weight: Option<u64>,
+}
+
+impl MerkleItem for KeyLeaf {
+    fn commit(&self, t: &mut Transcript) {
+        t.append_u64(LABEL_INDEX, self.position as u64);
+        t.append_message(b"X", self.key.as_bytes());
+        if let Some(w) = self.weight {
+            t.append_u64(b"w", w);
+        }
+    }
+}
+
+impl Multikey {
+    /// Returns the merkle root committing to the ordered list of keys (and weights, if any).
+    pub fn key_list_commitment(&self) -> Hash {
+        MerkleTree::root(AggregationProof::LABEL, self.leaves())
+    }
+
+    /// Returns the proof that the key of the party at the given `position`
+    /// is in the list committed to by `key_list_commitment`,
+    /// or `None` if the position is out of range.
+    pub fn aggregation_proof(&self, position: usize) -> Option<AggregationProof> {
+        if position >= self.len() {
+            return None;
+        }
+        let leaves: Vec<_> = self.leaves().collect();
+        let path = Path::new(&leaves, position, &Hasher::new(AggregationProof::LABEL))?;
+        Some(AggregationProof {
+            leaf: leaves[position],
+            path,
+        })
+    }
+
+    /// Checks that the `claimed_key` is aggregated from the keys of this multikey,
+    /// and that they are the list committed to by the `key_list_commitment`.
+    /// Fails with `MusigError::AggregatedKeyMismatch` if either does not match.
+    pub fn verify_key_list(
+        &self,
+        claimed_key: VerificationKey,
+        key_list_commitment: &Hash,
+    ) -> Result<(), MusigError> {
+        if &self.key_list_commitment() != key_list_commitment
+            || self.aggregated_key() != claimed_key
+        {
+            return Err(MusigError::AggregatedKeyMismatch);
+        }
+        Ok(())
+    }
+
+    fn leaves(&self) -> impl Iterator<Item = KeyLeaf> + '_ {
+        (0..self.len()).map(move |position| KeyLeaf {
+            position,
+            key: self.key(position),
+            weight: self.weights().map(|w| w[position]),
+        })
+    }
+}
+
+impl AggregationProof {
+    /// Returns the position of the key in the list.
+    pub fn position(&self) -> usize {
+        self.leaf.position
+    }
+
+    /// Returns the key at the position.
+    pub fn key(&self) -> VerificationKey {
+        self.leaf.key
+    }
+
+    /// Returns the weight of the key, if the multikey is weighted.
+    pub fn weight(&self) -> Option<u64> {
+        self.leaf.weight
+    }
+
+    /// Checks that the key (and its weight) is at its position of the list
+    /// committed to by the `key_list_commitment`.
+    /// Fails with `MusigError::AggregatedKeyMismatch` if the proof does not match the commitment.
+    pub fn verify(&self, key_list_commitment: &Hash) -> Result<(), MusigError> {
+        if self
+            .path
+            .verify_root(key_list_commitment, &self.leaf, &Hasher::new(Self::LABEL))
+        {
+            Ok(())
+        } else {
+            Err(MusigError::AggregatedKeyMismatch)
+        }
+    }
+
+    /// Encodes the proof as the LE64 position, the key, a flag byte (1 if weighted)
+    /// followed by the LE64 weight if weighted, and the merkle path.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+
+    /// Decodes the proof encoded with `to_bytes`.
+    /// Fails with `MusigError::InvalidFormat` if the encoding is malformed,
+    /// and with `MusigError::InvalidPoint` if the key is not a valid point.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, MusigError> {
+        bytes.read_all(|r| {
+            let position = r.read_u64()? as usize;
+            let key = read_key(r)?;
+            let weight = match r.read_u8()? {
+                0 => None,
+                1 => Some(r.read_u64()?),
+                _ => return Err(MusigError::InvalidFormat),
+            };
+            let path = Path::decode(r)?;
+            Ok(AggregationProof {
+                leaf: KeyLeaf {
+                    position,
+                    key,
+                    weight,
+                },
+                path,
+            })
+        })
+    }
+
+    const LABEL: &'static [u8] = b"Musig.key-list";
+}
+
+impl Encodable for AggregationProof {
+    fn encode(&self, w: &mut impl Writer) -> Result<(), WriteError> {
+        w.write_u64(b"position", self.leaf.position as u64)?;
+        w.write(b"X", self.leaf.key.as_bytes())?;
+        match self.leaf.weight {
+            None => w.write_u8(b"weighted", 0)?,
+            Some(weight) => {
+                w.write_u8(b"weighted", 1)?;
+                w.write_u64(b"w", weight)?;
+            }
+        }
+        self.path.encode(w)
+    }
+}
+
+impl ExactSizeEncodable for AggregationProof {
+    fn encoded_size(&self) -> usize {
+        8 + VerificationKey::SIZE
+            + 1
+            + self.leaf.weight.map(|_| 8).unwrap_or(0)
+            + self.path.encoded_size()
+    }
+}
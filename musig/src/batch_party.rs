@@ -0,0 +1,37 @@
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use super::{BatchSigner, BatchSignerAwaitingPrecommitments, Multikey, NoncePrecommitment};
+
+/// Signer that holds its secret key and the multikey once,
+/// and signs batches of messages with the same counterparties.
+/// The per-key factors are derived once by the multikey and reused for every message.
+pub struct BatchParty<'m> {
+    multikey: &'m Multikey,
+    position: usize,
+    x_i: Scalar,
+}
+
+impl<'m> BatchParty<'m> {
+    /// Creates a party at the given position in the multikey, with the secret key `x_i`.
+    pub fn new(multikey: &'m Multikey, position: usize, x_i: Scalar) -> Self {
+        BatchParty {
+            multikey,
+            position,
+            x_i,
+        }
+    }
+
+    /// Starts signing a batch of transcripts, one per message.
+    /// The remaining rounds are the same as in `BatchSigner`.
+    pub fn start<'t>(
+        &self,
+        // The messages have already been fed into the transcripts
+        transcripts: &'t mut [Transcript],
+    ) -> (
+        BatchSignerAwaitingPrecommitments<'t, &'m Multikey>,
+        Vec<NoncePrecommitment>,
+    ) {
+        BatchSigner::new(transcripts, self.position, self.x_i, self.multikey)
+    }
+}
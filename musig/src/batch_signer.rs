@@ -0,0 +1,152 @@
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use starsig::Signature;
+use std::collections::BTreeSet;
+
+use super::{
+    MusigContext, NonceCommitment, NoncePrecommitment, Signer, SignerAwaitingCommitments,
+    SignerAwaitingPrecommitments, SignerAwaitingShares, SigningError,
+};
+
+/// Entry point to signing several messages with the same context in one session.
+/// Each message is signed with an independent nonce, and the messages of every round
+/// are sent together: each party sends one message per signed transcript.
+/// The context (e.g. the aggregated key of a `Multikey`) is computed once for all the messages.
+pub struct BatchSigner {}
+
+/// State of the party when awaiting nonce precommitments for all the messages.
+pub struct BatchSignerAwaitingPrecommitments<'t, C: MusigContext> {
+    signers: Vec<SignerAwaitingPrecommitments<'t, C>>,
+    parties: usize,
+}
+
+/// State of the party when awaiting nonce commitments for all the messages.
+pub struct BatchSignerAwaitingCommitments<'t, C: MusigContext> {
+    signers: Vec<SignerAwaitingCommitments<'t, C>>,
+    parties: usize,
+}
+
+/// State of the party when awaiting signature shares for all the messages.
+pub struct BatchSignerAwaitingShares<C: MusigContext> {
+    signers: Vec<SignerAwaitingShares<C>>,
+    parties: usize,
+}
+
+impl BatchSigner {
+    /// Create new signing party for the given transcripts, one per message.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<'t, C: MusigContext + Clone>(
+        // The messages have already been fed into the transcripts
+        transcripts: &'t mut [Transcript],
+        position: usize,
+        x_i: Scalar,
+        context: C,
+    ) -> (
+        BatchSignerAwaitingPrecommitments<'t, C>,
+        Vec<NoncePrecommitment>,
+    ) {
+        let parties = context.len();
+        let (signers, precommitments) = transcripts
+            .iter_mut()
+            .map(|transcript| Signer::new(transcript, position, x_i, context.clone()))
+            .unzip();
+        (
+            BatchSignerAwaitingPrecommitments { signers, parties },
+            precommitments,
+        )
+    }
+}
+
+impl<'t, C: MusigContext> BatchSignerAwaitingPrecommitments<'t, C> {
+    /// Provide nonce precommitments from all the parties (indexed by party, then by message)
+    /// and transition to the next round.
+    pub fn receive_precommitments(
+        self,
+        nonce_precommitments: &[Vec<NoncePrecommitment>],
+    ) -> Result<(BatchSignerAwaitingCommitments<'t, C>, Vec<NonceCommitment>), SigningError> {
+        let parties = self.parties;
+        let per_message = transpose(nonce_precommitments, parties, self.signers.len())?;
+        let (signers, commitments) = self
+            .signers
+            .into_iter()
+            .zip(per_message)
+            .map(|(signer, precommitments)| signer.receive_precommitments(&precommitments))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        Ok((
+            BatchSignerAwaitingCommitments { signers, parties },
+            commitments,
+        ))
+    }
+}
+
+impl<'t, C: MusigContext> BatchSignerAwaitingCommitments<'t, C> {
+    /// Provide nonce commitments from all the parties (indexed by party, then by message)
+    /// and transition to the next round if they match the precommitments.
+    /// Fails with `SigningError::NonceReuse` if any nonce commitment occurs more than once
+    /// in the session, e.g. when a coordinator replays a commitment for another message.
+    pub fn receive_commitments(
+        self,
+        nonce_commitments: &[Vec<NonceCommitment>],
+    ) -> Result<(BatchSignerAwaitingShares<C>, Vec<Scalar>), SigningError> {
+        let parties = self.parties;
+        let per_message = transpose(nonce_commitments, parties, self.signers.len())?;
+        check_nonce_reuse(nonce_commitments)?;
+        let (signers, shares) = self
+            .signers
+            .into_iter()
+            .zip(per_message)
+            .map(|(signer, commitments)| signer.receive_commitments(&commitments))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        Ok((BatchSignerAwaitingShares { signers, parties }, shares))
+    }
+}
+
+impl<C: MusigContext> BatchSignerAwaitingShares<C> {
+    /// Verify and assemble signature shares from all the parties (indexed by party, then by message)
+    /// into signatures, one per message.
+    pub fn receive_shares(self, shares: &[Vec<Scalar>]) -> Result<Vec<Signature>, SigningError> {
+        let per_message = transpose(shares, self.parties, self.signers.len())?;
+        self.signers
+            .into_iter()
+            .zip(per_message)
+            .map(|(signer, shares)| signer.receive_shares(&shares))
+            .collect()
+    }
+}
+
+/// Checks that every nonce commitment in the session is used once.
+/// Independently generated nonces never collide, so a repeated commitment
+/// means that a nonce is being reused across messages, which can leak the secret key.
+fn check_nonce_reuse(nonce_commitments: &[Vec<NonceCommitment>]) -> Result<(), SigningError> {
+    let mut used = BTreeSet::new();
+    for commitment in nonce_commitments.iter().flatten() {
+        if !used.insert(commitment.into_point().to_bytes()) {
+            return Err(SigningError::NonceReuse);
+        }
+    }
+    Ok(())
+}
+
+/// Regroups messages received from each of the `parties` by the signed message.
+/// Fails with `SigningError::MissingParticipants` unless there are messages from every party,
+/// and with `SigningError::BatchSizeMismatch` unless every party sent `n` messages.
+fn transpose<T: Copy>(
+    per_party: &[Vec<T>],
+    parties: usize,
+    n: usize,
+) -> Result<Vec<Vec<T>>, SigningError> {
+    SigningError::check_count(parties, per_party.len())?;
+    if let Some(messages) = per_party.iter().find(|messages| messages.len() != n) {
+        return Err(SigningError::BatchSizeMismatch {
+            expected: n,
+            received: messages.len(),
+        });
+    }
+    Ok((0..n)
+        .map(|j| per_party.iter().map(|messages| messages[j]).collect())
+        .collect())
+}
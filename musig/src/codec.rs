@@ -0,0 +1,176 @@
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use readerwriter::{Reader, Writer};
+use starsig::Signature;
+
+use super::{
+    MusigContext, MusigError, NonceCommitment, NoncePrecommitment, SignerAwaitingCommitments,
+    SignerAwaitingPrecommitments, SignerAwaitingShares,
+};
+
+/// Message of the multi-party signing protocol, sent by a signer to its counterparties.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SignerMessage {
+    /// Nonce precommitment, sent in the first round.
+    Precommitment(NoncePrecommitment),
+    /// Nonce commitment, sent in the second round.
+    Commitment(NonceCommitment),
+    /// Signature share, sent in the third round.
+    Share(Scalar),
+}
+
+/// Framing of the protocol messages for transport over a byte stream.
+/// Each frame is a 1-byte message type, followed by LE32-encoded length of the payload,
+/// and the payload itself.
+/// The frames of a round are passed to the signer with `SignerState::receive_frames`,
+/// which dispatches them to the `receive_*` call of the current signer state.
+pub struct SignerCodec {}
+
+const PRECOMMITMENT_TAG: u8 = 1;
+const COMMITMENT_TAG: u8 = 2;
+const SHARE_TAG: u8 = 3;
+
+/// Size of the payload of every message type: a precommitment hash,
+/// a compressed point or a scalar.
+const PAYLOAD_LEN: usize = 32;
+
+impl SignerCodec {
+    /// Encodes the message as a frame.
+    pub fn encode_message(message: &SignerMessage) -> Vec<u8> {
+        let (tag, payload) = match message {
+            SignerMessage::Precommitment(p) => (PRECOMMITMENT_TAG, p.to_bytes()),
+            SignerMessage::Commitment(c) => (COMMITMENT_TAG, c.into_point().to_bytes()),
+            SignerMessage::Share(s) => (SHARE_TAG, s.to_bytes()),
+        };
+        let mut buf = Vec::with_capacity(1 + 4 + payload.len());
+        buf.write_u8(b"type", tag)
+            .and_then(|_| buf.write_u32(b"len", payload.len() as u32))
+            .and_then(|_| buf.write(b"payload", &payload))
+            .expect("Writing to a Vec never fails.");
+        buf
+    }
+
+    /// Decodes the next frame from the stream buffer, advancing it past the frame.
+    /// Fails if the frame is truncated, has an unknown type, a length that does not match its type,
+    /// or an invalid payload. On failure the stream is left at the start of the frame.
+    pub fn decode_message(stream: &mut &[u8]) -> Result<SignerMessage, MusigError> {
+        // Read the whole frame from a copy of the stream before parsing it,
+        // so that the stream is advanced only past a valid frame.
+        let mut frame = *stream;
+        let tag = frame.read_u8()?;
+        let len = frame.read_u32()? as usize;
+        if !matches!(tag, PRECOMMITMENT_TAG | COMMITMENT_TAG | SHARE_TAG) || len != PAYLOAD_LEN {
+            return Err(MusigError::InvalidFormat);
+        }
+        let payload = frame.read_u8x32()?;
+        let message = match tag {
+            PRECOMMITMENT_TAG => {
+                SignerMessage::Precommitment(NoncePrecommitment::from_bytes(payload))
+            }
+            COMMITMENT_TAG => {
+                SignerMessage::Commitment(NonceCommitment::from_point(CompressedRistretto(payload)))
+            }
+            _ => Scalar::from_canonical_bytes(payload)
+                .map(SignerMessage::Share)
+                .ok_or(MusigError::InvalidFormat)?,
+        };
+        *stream = frame;
+        Ok(message)
+    }
+
+    /// Decodes all the frames in the stream buffer.
+    /// Fails if any of the frames fails to decode.
+    pub fn decode_all(mut stream: &[u8]) -> Result<Vec<SignerMessage>, MusigError> {
+        let mut messages = Vec::new();
+        while !stream.is_empty() {
+            messages.push(Self::decode_message(&mut stream)?);
+        }
+        Ok(messages)
+    }
+}
+
+/// State of the signer driven by the frames received from the byte stream.
+pub enum SignerState<'t, C: MusigContext> {
+    /// Awaiting the precommitment frames.
+    AwaitingPrecommitments(SignerAwaitingPrecommitments<'t, C>),
+    /// Awaiting the commitment frames.
+    AwaitingCommitments(SignerAwaitingCommitments<'t, C>),
+    /// Awaiting the share frames.
+    AwaitingShares(SignerAwaitingShares<C>),
+    /// The protocol is complete.
+    Done(Signature),
+}
+
+impl<'t, C: MusigContext> SignerState<'t, C> {
+    /// Decodes the frames of the current round, one per party ordered by position,
+    /// and passes them to the `receive_*` call of the current state.
+    /// Returns the next state, and the frame with the party's message for the next round, if any.
+    /// Fails with `MusigError::InvalidFormat` if a frame fails to decode or has a type
+    /// not expected in the current state, or if the protocol is complete.
+    pub fn receive_frames(self, stream: &[u8]) -> Result<(Self, Option<Vec<u8>>), MusigError> {
+        let messages = SignerCodec::decode_all(stream)?;
+        match self {
+            SignerState::AwaitingPrecommitments(signer) => {
+                let precommitments = messages
+                    .into_iter()
+                    .map(SignerMessage::into_precommitment)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let (signer, commitment) = signer.receive_precommitments(&precommitments)?;
+                Ok((
+                    SignerState::AwaitingCommitments(signer),
+                    Some(SignerCodec::encode_message(&SignerMessage::Commitment(
+                        commitment,
+                    ))),
+                ))
+            }
+            SignerState::AwaitingCommitments(signer) => {
+                let commitments = messages
+                    .into_iter()
+                    .map(SignerMessage::into_commitment)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let (signer, share) = signer.receive_commitments(&commitments)?;
+                Ok((
+                    SignerState::AwaitingShares(signer),
+                    Some(SignerCodec::encode_message(&SignerMessage::Share(share))),
+                ))
+            }
+            SignerState::AwaitingShares(signer) => {
+                let shares = messages
+                    .into_iter()
+                    .map(SignerMessage::into_share)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((SignerState::Done(signer.receive_shares(&shares)?), None))
+            }
+            SignerState::Done(_) => Err(MusigError::InvalidFormat),
+        }
+    }
+}
+
+impl SignerMessage {
+    /// Returns the precommitment for `SignerAwaitingPrecommitments::receive_precommitments`,
+    /// or fails if the message has a different type.
+    pub fn into_precommitment(self) -> Result<NoncePrecommitment, MusigError> {
+        match self {
+            SignerMessage::Precommitment(p) => Ok(p),
+            _ => Err(MusigError::InvalidFormat),
+        }
+    }
+
+    /// Returns the commitment for `SignerAwaitingCommitments::receive_commitments`,
+    /// or fails if the message has a different type.
+    pub fn into_commitment(self) -> Result<NonceCommitment, MusigError> {
+        match self {
+            SignerMessage::Commitment(c) => Ok(c),
+            _ => Err(MusigError::InvalidFormat),
+        }
+    }
+
+    /// Returns the share for `SignerAwaitingShares::receive_shares`,
+    /// or fails if the message has a different type.
+    pub fn into_share(self) -> Result<Scalar, MusigError> {
+        match self {
+            SignerMessage::Share(s) => Ok(s),
+            _ => Err(MusigError::InvalidFormat),
+        }
+    }
+}
@@ -1,8 +1,14 @@
-use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
 use merlin::Transcript;
-use starsig::{SigningKey, TranscriptProtocol as StarsigTranscriptProtocol, VerificationKey};
 
+use starsig::{
+    BatchVerifier, Signature, SigningKey, TranscriptProtocol as StarsigTranscriptProtocol,
+    VerificationKey, LABEL_CHALLENGE, LABEL_KEY,
+};
+
+use super::transcript::{LABEL_AGGREGATION, LABEL_COEFFICIENT, LABEL_INDEX, LABEL_KEY_COUNT};
 use super::{MusigError, TranscriptProtocol};
 
 /// The context for signing - can either be a Multikey or Multimessage context.
@@ -24,9 +30,13 @@ pub trait MusigContext {
 /// MuSig aggregated key context
 #[derive(Clone)]
 pub struct Multikey {
-    prf: Option<Transcript>,
+    /// Per-key factors `w_i * a_i`, derived once when the keys are aggregated.
+    factors: Vec<Scalar>,
     aggregated_key: VerificationKey,
     public_keys: Vec<VerificationKey>,
+    weights: Option<Vec<u64>>,
+    /// Display labels of the keys, not committed to the aggregated key.
+    labels: Option<Vec<String>>,
 }
 
 /// MuSig multimessage context
@@ -37,54 +47,301 @@ pub struct Multimessage<M: AsRef<[u8]>> {
 
 impl Multikey {
     /// Constructs a new MuSig multikey aggregating the pubkeys.
+    /// All the keys are decompressed up front: an invalid key fails with
+    /// `MusigError::InvalidKey` reporting its index.
     pub fn new(pubkeys: Vec<VerificationKey>) -> Result<Self, MusigError> {
         match pubkeys.len() {
             0 => {
                 return Err(MusigError::BadArguments);
             }
             1 => {
+                Multikey::decompress_keys(&pubkeys)?;
                 // Special case: single key can be wrapped in a Multikey type
                 // without a delinearization factor applied.
                 return Ok(Multikey {
-                    prf: None,
+                    factors: vec![Scalar::one()],
                     aggregated_key: pubkeys[0],
                     public_keys: pubkeys,
+                    weights: None,
+                    labels: None,
                 });
             }
             _ => {}
         }
 
-        // Create transcript for Multikey
-        let mut prf = Transcript::new(b"Musig.aggregated-key");
-        prf.append_u64(b"n", pubkeys.len() as u64);
+        Multikey::check_duplicates(&pubkeys)?;
+        let points = Multikey::decompress_keys(&pubkeys)?;
+        let prf = Multikey::transcript_for_keys(&pubkeys);
+
+        Multikey::aggregate(prf, pubkeys, points, None)
+    }
+
+    /// Creates transcript for Multikey.
+    /// Committing the number of keys and the keys themselves binds
+    /// the aggregated key (and hence the signature challenge) to the exact set of participants.
+    pub(super) fn transcript_for_keys(pubkeys: &[VerificationKey]) -> Transcript {
+        let mut prf = Transcript::new(LABEL_AGGREGATION);
+        prf.append_u64(LABEL_KEY_COUNT, pubkeys.len() as u64);
 
         // Commit pubkeys into the transcript
         // <L> = H(X_1 || X_2 || ... || X_n)
-        for X in &pubkeys {
-            prf.append_point(b"X", X.as_point());
+        for X in pubkeys {
+            prf.append_point(LABEL_KEY, X.as_point());
         }
+        prf
+    }
 
-        // aggregated_key = sum_i ( a_i * X_i )
-        let mut aggregated_key = RistrettoPoint::default();
-        for (i, X) in pubkeys.iter().enumerate() {
-            let a = Multikey::compute_factor(&prf, i);
-            let X = X.as_point().decompress().ok_or(MusigError::InvalidPoint)?;
-            aggregated_key = aggregated_key + a * X;
+    /// Constructs a new MuSig multikey aggregating the pubkeys like `new`,
+    /// keeping a display label for each key (e.g. a participant's name).
+    /// The labels are not committed to the aggregated key,
+    /// so it is identical to the one of the multikey without labels.
+    pub fn new_labeled(
+        keys_and_labels: Vec<(VerificationKey, String)>,
+    ) -> Result<Self, MusigError> {
+        let (pubkeys, labels): (Vec<_>, Vec<_>) = keys_and_labels.into_iter().unzip();
+        let mut multikey = Multikey::new(pubkeys)?;
+        multikey.labels = Some(labels);
+        Ok(multikey)
+    }
+
+    /// Returns the label of the `key`, if the multikey was created with `new_labeled`
+    /// and the key is one of its keys.
+    pub fn label_for(&self, key: &VerificationKey) -> Option<&str> {
+        let index = self.public_keys.iter().position(|X| X == key)?;
+        self.labels.as_ref().map(|labels| labels[index].as_str())
+    }
+
+    /// Constructs a new MuSig multikey aggregating the pubkeys with the given weights
+    /// (e.g. proportional to stake): the aggregated key is `sum_i( w_i * a_i * X_i )`.
+    /// Parties sign with the usual `Signer` protocol, which scales their shares by `w_i`.
+    /// The weights are committed together with the keys, so a share computed
+    /// with different weights fails to verify.
+    pub fn new_weighted(keys_and_weights: &[(VerificationKey, u64)]) -> Result<Self, MusigError> {
+        if keys_and_weights.is_empty() || keys_and_weights.iter().any(|(_, w)| *w == 0) {
+            return Err(MusigError::BadArguments);
+        }
+        let (pubkeys, weights): (Vec<_>, Vec<_>) = keys_and_weights.iter().cloned().unzip();
+        Multikey::check_duplicates(&pubkeys)?;
+        let points = Multikey::decompress_keys(&pubkeys)?;
+
+        // Weighted multikey uses a separate transcript, that commits the weights too.
+        let mut prf = Transcript::new(b"Musig.weighted-key");
+        prf.append_u64(LABEL_KEY_COUNT, pubkeys.len() as u64);
+        for (X, w) in keys_and_weights {
+            prf.append_point(LABEL_KEY, X.as_point());
+            prf.append_u64(b"w", *w);
+        }
+
+        Multikey::aggregate(prf, pubkeys, points, Some(weights))
+    }
+
+    /// Recomputes the aggregated key from the participants' keys and checks that it matches
+    /// the `claimed` aggregated key, e.g. one received from an untrusted coordinator.
+    pub fn verify_aggregate_matches(
+        pubkeys: &[VerificationKey],
+        claimed: VerificationKey,
+    ) -> Result<(), MusigError> {
+        if aggregate_keys(pubkeys)? != claimed {
+            return Err(MusigError::AggregatedKeyMismatch);
         }
+        Ok(())
+    }
+
+    /// Verifies signatures by different multikeys in a single batch.
+    /// Each item is a multikey, a message and a signature by its aggregated key,
+    /// made over the transcript that `Signature::sign_message` creates for the `label` and the message:
+    /// a transcript labelled "Starsig.sign_message" with the message appended under `label`.
+    pub fn verify_batch(
+        label: &'static [u8],
+        items: &[(&Multikey, &[u8], &Signature)],
+    ) -> Result<(), MusigError> {
+        let mut batch = BatchVerifier::with_capacity(rand::thread_rng(), items.len());
+        for (multikey, message, signature) in items {
+            signature.verify_message_batched(label, message, multikey.aggregated_key(), &mut batch);
+        }
+        Ok(batch.verify()?)
+    }
+
+    /// Creates a multikey with the key `old` replaced by `new` at the same position,
+    /// e.g. when a participant rotates its key. The coefficients of all the keys
+    /// are recomputed, so the aggregated key changes, and the weights and labels are preserved
+    /// (the new key takes the label of the old one).
+    /// Returns `MusigError::BadArguments` if `old` is not one of the keys.
+    pub fn rotate_key(
+        &self,
+        old: &VerificationKey,
+        new: VerificationKey,
+    ) -> Result<Multikey, MusigError> {
+        let index = self
+            .public_keys
+            .iter()
+            .position(|X| X == old)
+            .ok_or(MusigError::BadArguments)?;
+        let mut pubkeys = self.public_keys.clone();
+        pubkeys[index] = new;
+
+        let mut multikey = match &self.weights {
+            Some(weights) => {
+                let keys_and_weights: Vec<_> =
+                    pubkeys.into_iter().zip(weights.iter().copied()).collect();
+                Multikey::new_weighted(&keys_and_weights)?
+            }
+            None => Multikey::new(pubkeys)?,
+        };
+        multikey.labels = self.labels.clone();
+        Ok(multikey)
+    }
+
+    /// Verifies the `signature` by the subset of the parties marked in the `signed` bitmap,
+    /// and checks that their summed weight meets the `threshold`
+    /// (each key has weight 1 if the multikey is not weighted).
+    /// The aggregated key of the signing subset is recomputed from its keys (and weights),
+    /// and the signature is made over the transcript that `Signature::sign_message` creates
+    /// for the `label` and the `message`, like in `verify_batch`.
+    /// Fails with `MusigError::BadArguments` if the bitmap does not match the number of keys,
+    /// if no party signed or if their summed weight overflows,
+    /// and with `MusigError::QuorumNotMet` if the weight is below the threshold.
+    pub fn verify_weighted_quorum(
+        &self,
+        signed: &[bool],
+        threshold: u64,
+        label: &'static [u8],
+        message: &[u8],
+        signature: &Signature,
+    ) -> Result<(), MusigError> {
+        if signed.len() != self.public_keys.len() {
+            return Err(MusigError::BadArguments);
+        }
+        let signers: Vec<(VerificationKey, u64)> = (0..self.public_keys.len())
+            .filter(|i| signed[*i])
+            .map(|i| {
+                (
+                    self.public_keys[i],
+                    self.weights.as_ref().map_or(1, |w| w[i]),
+                )
+            })
+            .collect();
+        let weight = signers
+            .iter()
+            .try_fold(0u64, |sum, (_, w)| sum.checked_add(*w))
+            .ok_or(MusigError::BadArguments)?;
+        if weight < threshold {
+            return Err(MusigError::QuorumNotMet { weight, threshold });
+        }
+
+        let subset = match self.weights {
+            Some(_) => Multikey::new_weighted(&signers)?,
+            None => Multikey::new(signers.into_iter().map(|(X, _)| X).collect())?,
+        };
+        Ok(signature.verify_message(label, message, subset.aggregated_key())?)
+    }
+
+    /// Returns the weights of the keys, if the multikey was created with `new_weighted`.
+    pub fn weights(&self) -> Option<&[u64]> {
+        self.weights.as_deref()
+    }
+
+    /// Rejects repeated keys: a party listed several times would contribute
+    /// several shares with the same key, which is most likely a misconfiguration.
+    fn check_duplicates(pubkeys: &[VerificationKey]) -> Result<(), MusigError> {
+        let mut keys: Vec<_> = pubkeys.iter().map(|X| X.to_bytes()).collect();
+        Multikey::check_duplicate_bytes(&mut keys)
+    }
+
+    /// Rejects repeated keys among the encoded `keys`, like `check_duplicates`.
+    /// The keys are sorted in place, so that the check does not allocate.
+    pub(super) fn check_duplicate_bytes(keys: &mut [[u8; 32]]) -> Result<(), MusigError> {
+        keys.sort_unstable();
+        match keys.windows(2).find(|pair| pair[0] == pair[1]) {
+            Some(pair) => Err(MusigError::DuplicateKey { pubkey: pair[0] }),
+            None => Ok(()),
+        }
+    }
+
+    pub(super) fn decompress_keys(
+        pubkeys: &[VerificationKey],
+    ) -> Result<Vec<RistrettoPoint>, MusigError> {
+        pubkeys
+            .iter()
+            .enumerate()
+            .map(|(index, X)| Multikey::decompress_key(index, X))
+            .collect()
+    }
+
+    /// Decompresses the key at the `index`, failing with `MusigError::InvalidKey`.
+    pub(super) fn decompress_key(
+        index: usize,
+        X: &VerificationKey,
+    ) -> Result<RistrettoPoint, MusigError> {
+        X.as_point()
+            .decompress()
+            .ok_or(MusigError::InvalidKey { index })
+    }
+
+    /// Aggregates the keys committed to `prf` given their decompressed `points`.
+    pub(super) fn aggregate(
+        prf: Transcript,
+        pubkeys: Vec<VerificationKey>,
+        points: Vec<RistrettoPoint>,
+        weights: Option<Vec<u64>>,
+    ) -> Result<Self, MusigError> {
+        // aggregated_key = sum_i ( w_i * a_i * X_i ), with w_i = 1 for unweighted keys
+        let mut factors = vec![Scalar::zero(); pubkeys.len()];
+        Multikey::compute_factors(&prf, weights.as_deref(), &mut factors);
+        let aggregated_key = RistrettoPoint::vartime_multiscalar_mul(&factors, &points);
+
+        #[cfg(debug_assertions)]
+        Multikey::debug_assert_aggregation(&factors, &points, &aggregated_key);
 
         Ok(Multikey {
-            prf: Some(prf),
+            factors,
             aggregated_key: VerificationKey::from(aggregated_key),
             public_keys: pubkeys,
+            weights,
+            labels: None,
         })
     }
 
+    /// Computes the per-key factors `w_i * a_i` of the keys committed to `prf` into `factors`,
+    /// with `w_i = 1` for unweighted keys.
+    pub(super) fn compute_factors(
+        prf: &Transcript,
+        weights: Option<&[u64]>,
+        factors: &mut [Scalar],
+    ) {
+        for (i, factor) in factors.iter_mut().enumerate() {
+            let w_i = match weights {
+                Some(w) => Scalar::from(w[i]),
+                None => Scalar::one(),
+            };
+            *factor = Multikey::compute_factor(prf, i) * w_i;
+        }
+    }
+
     /// Returns `a_i` factor for component key in aggregated key.
     /// a_i = H(<L>, X_i). The list of pubkeys, <L>, has already been committed to the transcript.
-    fn compute_factor(prf: &Transcript, i: usize) -> Scalar {
+    pub(super) fn compute_factor(prf: &Transcript, i: usize) -> Scalar {
         let mut a_i_prf = prf.clone();
-        a_i_prf.append_u64(b"i", i as u64);
-        a_i_prf.challenge_scalar(b"a_i")
+        a_i_prf.append_u64(LABEL_INDEX, i as u64);
+        a_i_prf.challenge_scalar(LABEL_COEFFICIENT)
+    }
+
+    /// Recomputes the aggregated key with a naive loop and checks that it matches
+    /// the result of the multiscalar multiplication.
+    #[cfg(debug_assertions)]
+    pub(super) fn debug_assert_aggregation(
+        factors: &[Scalar],
+        points: &[RistrettoPoint],
+        aggregated_key: &RistrettoPoint,
+    ) {
+        let mut expected_key = RistrettoPoint::default();
+        for (a, X) in factors.iter().zip(points.iter()) {
+            expected_key += a * X;
+        }
+        assert_eq!(
+            &expected_key, aggregated_key,
+            "Aggregated key does not match the sum of delinearized keys"
+        );
     }
 
     /// Returns VerificationKey representation of aggregated key.
@@ -108,47 +365,116 @@ impl Multikey {
         }
 
         // Create transcript for Multikey
-        let mut prf = Transcript::new(b"Musig.aggregated-key");
-        prf.append_u64(b"n", privkeys.len() as u64);
+        let mut prf = Transcript::new(LABEL_AGGREGATION);
+        prf.append_u64(LABEL_KEY_COUNT, privkeys.len() as u64);
 
         // Commit pubkeys into the transcript
         // <L> = H(X_1 || X_2 || ... || X_n)
         for x in privkeys.iter() {
             let X = VerificationKey::from_secret(x);
-            prf.append_point(b"X", X.as_point());
+            prf.append_point(LABEL_KEY, X.as_point());
         }
 
         // aggregated_key = sum_i ( a_i * X_i )
         let mut aggregated_key = Scalar::zero();
         for (i, x) in privkeys.iter().enumerate() {
             let a = Multikey::compute_factor(&prf, i);
-            aggregated_key = aggregated_key + a * x;
+            aggregated_key += a * x;
         }
 
         aggregated_key
     }
 }
 
+/// Computes the aggregated key of the pubkeys, equal to `Multikey::new(pubkeys).aggregated_key()`,
+/// without keeping the list of keys and the transcript needed for signing.
+/// This is useful for light clients that only verify signatures.
+/// The keys are validated like in `Multikey::new`, failing with the same errors.
+pub fn aggregate_keys(pubkeys: &[VerificationKey]) -> Result<VerificationKey, MusigError> {
+    match pubkeys.len() {
+        0 => return Err(MusigError::BadArguments),
+        1 => {
+            Multikey::decompress_keys(pubkeys)?;
+            return Ok(pubkeys[0]);
+        }
+        _ => {}
+    }
+    Multikey::check_duplicates(pubkeys)?;
+    let points = Multikey::decompress_keys(pubkeys)?;
+    let prf = Multikey::transcript_for_keys(pubkeys);
+
+    // aggregated_key = sum_i ( a_i * X_i )
+    let aggregated_key = RistrettoPoint::vartime_multiscalar_mul(
+        (0..pubkeys.len()).map(|i| Multikey::compute_factor(&prf, i)),
+        &points,
+    );
+
+    Ok(VerificationKey::from(aggregated_key))
+}
+
+/// Computes the aggregated keys of many independent groups of pubkeys,
+/// each equal to `aggregate_keys` of the group, e.g. for a coordinator of many signing groups.
+/// The buffers for the factors and the decompressed keys are allocated once and reused across the groups.
+/// Fails on the first invalid group with the same error as `aggregate_keys`.
+pub fn aggregate_many(groups: &[&[VerificationKey]]) -> Result<Vec<VerificationKey>, MusigError> {
+    let mut factors = Vec::new();
+    let mut points = Vec::new();
+    groups
+        .iter()
+        .map(|pubkeys| {
+            match pubkeys.len() {
+                0 => return Err(MusigError::BadArguments),
+                1 => {
+                    Multikey::decompress_keys(pubkeys)?;
+                    return Ok(pubkeys[0]);
+                }
+                _ => {}
+            }
+            Multikey::check_duplicates(pubkeys)?;
+            points.clear();
+            for (index, X) in pubkeys.iter().enumerate() {
+                points.push(Multikey::decompress_key(index, X)?);
+            }
+
+            let prf = Multikey::transcript_for_keys(pubkeys);
+            factors.clear();
+            factors.extend((0..pubkeys.len()).map(|i| Multikey::compute_factor(&prf, i)));
+
+            // aggregated_key = sum_i ( a_i * X_i )
+            let aggregated_key = RistrettoPoint::vartime_multiscalar_mul(&factors, &points);
+            Ok(VerificationKey::from(aggregated_key))
+        })
+        .collect()
+}
+
+/// Computes the aggregated key like `aggregate_keys`, operating directly on compressed keys.
+/// Every key is checked to decompress: an invalid key fails with `MusigError::InvalidKey`
+/// reporting its index.
+pub fn aggregate_keys_bytes(pubkeys: &[[u8; 32]]) -> Result<[u8; 32], MusigError> {
+    let pubkeys: Vec<_> = pubkeys
+        .iter()
+        .map(|bytes| VerificationKey::from_compressed(CompressedRistretto(*bytes)))
+        .collect();
+    Ok(aggregate_keys(&pubkeys)?.to_bytes())
+}
+
 impl MusigContext for Multikey {
     fn commit(&self, transcript: &mut Transcript) {
         transcript.starsig_domain_sep();
-        transcript.append_point(b"X", self.aggregated_key.as_point());
+        transcript.append_point(LABEL_KEY, self.aggregated_key.as_point());
     }
 
     fn challenge(&self, i: usize, transcript: &mut Transcript) -> Scalar {
         // Make c = H(X, R, m)
         // The message `m`, nonce commitment `R`, and aggregated key `X`
         // have already been fed into the transcript.
-        let c = transcript.challenge_scalar(b"c");
-
-        // Make a_i, the per-party factor. a_i = H(<L>, X_i).
-        // The list of pubkeys, <L>, has already been committed to self.transcript.
-        let a_i = match &self.prf {
-            Some(t) => Multikey::compute_factor(&t, i),
-            None => Scalar::one(),
-        };
+        let c = transcript.challenge_scalar(LABEL_CHALLENGE);
 
-        c * a_i
+        // Use a_i, the per-party factor. a_i = H(<L>, X_i),
+        // scaled by the party's weight w_i in a weighted multikey.
+        // The factors were computed when the keys were aggregated,
+        // so they are not re-derived for each challenge.
+        c * self.factors[i]
     }
 
     fn len(&self) -> usize {
@@ -171,15 +497,15 @@ impl<M: AsRef<[u8]>> MusigContext for Multimessage<M> {
     fn commit(&self, transcript: &mut Transcript) {
         transcript.musig_multimessage_domain_sep(self.pairs.len());
         for (key, msg) in &self.pairs {
-            transcript.append_point(b"X", key.as_point());
+            transcript.append_point(LABEL_KEY, key.as_point());
             transcript.append_message(b"m", msg.as_ref());
         }
     }
 
     fn challenge(&self, i: usize, transcript: &mut Transcript) -> Scalar {
         let mut transcript_i = transcript.clone();
-        transcript_i.append_u64(b"i", i as u64);
-        transcript_i.challenge_scalar(b"c")
+        transcript_i.append_u64(LABEL_INDEX, i as u64);
+        transcript_i.challenge_scalar(LABEL_CHALLENGE)
 
         // TBD: Do we want to add a domain separator to the transcript?
     }
@@ -192,3 +518,22 @@ impl<M: AsRef<[u8]>> MusigContext for Multimessage<M> {
         self.pairs[index].0
     }
 }
+
+/// Borrowed context, so that the signers can share one context without cloning it.
+impl<C: MusigContext + ?Sized> MusigContext for &C {
+    fn commit(&self, transcript: &mut Transcript) {
+        (**self).commit(transcript)
+    }
+
+    fn challenge(&self, index: usize, transcript: &mut Transcript) -> Scalar {
+        (**self).challenge(index, transcript)
+    }
+
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn key(&self, index: usize) -> VerificationKey {
+        (**self).key(index)
+    }
+}
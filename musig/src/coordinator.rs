@@ -0,0 +1,111 @@
+//! Async coordinator that collects the messages of each round of a signing session,
+//! enabled with the `async` feature.
+//!
+//! The coordinator only orchestrates: signers still run the synchronous state machine,
+//! and await the coordinator to learn the messages of the other parties.
+
+use std::sync::Mutex;
+
+use curve25519_dalek::scalar::Scalar;
+use tokio::sync::watch;
+
+use super::counterparty::{NonceCommitment, NoncePrecommitment};
+use super::errors::MusigError;
+
+/// Coordinator of a signing session among `n` parties.
+/// Each `submit_*` future resolves with the messages of all parties,
+/// once every party has submitted its message for that round.
+pub struct AsyncCoordinator {
+    precommitments: Round<NoncePrecommitment>,
+    commitments: Round<NonceCommitment>,
+    shares: Round<Scalar>,
+}
+
+/// Messages of a single round, published to all the waiting parties
+/// through a watch channel when the last message arrives.
+struct Round<T> {
+    slots: Mutex<Vec<Option<T>>>,
+    sender: watch::Sender<Option<Vec<T>>>,
+    receiver: watch::Receiver<Option<Vec<T>>>,
+}
+
+impl AsyncCoordinator {
+    /// Creates a coordinator for a session among `n` parties.
+    pub fn new(n: usize) -> Self {
+        AsyncCoordinator {
+            precommitments: Round::new(n),
+            commitments: Round::new(n),
+            shares: Round::new(n),
+        }
+    }
+
+    /// Submits the nonce precommitment of the party at `position`,
+    /// and resolves with the precommitments of all the parties.
+    pub async fn submit_precommitment(
+        &self,
+        position: usize,
+        precommitment: NoncePrecommitment,
+    ) -> Result<Vec<NoncePrecommitment>, MusigError> {
+        self.precommitments.submit(position, precommitment).await
+    }
+
+    /// Submits the nonce commitment of the party at `position`,
+    /// and resolves with the commitments of all the parties.
+    pub async fn submit_commitment(
+        &self,
+        position: usize,
+        commitment: NonceCommitment,
+    ) -> Result<Vec<NonceCommitment>, MusigError> {
+        self.commitments.submit(position, commitment).await
+    }
+
+    /// Submits the signature share of the party at `position`,
+    /// and resolves with the shares of all the parties.
+    pub async fn submit_share(
+        &self,
+        position: usize,
+        share: Scalar,
+    ) -> Result<Vec<Scalar>, MusigError> {
+        self.shares.submit(position, share).await
+    }
+}
+
+impl<T: Clone> Round<T> {
+    fn new(n: usize) -> Self {
+        let (sender, receiver) = watch::channel(None);
+        Round {
+            slots: Mutex::new(vec![None; n]),
+            sender,
+            receiver,
+        }
+    }
+
+    async fn submit(&self, position: usize, message: T) -> Result<Vec<T>, MusigError> {
+        let mut receiver = self.receiver.clone();
+        {
+            let mut slots = self.slots.lock().map_err(|_| MusigError::BadArguments)?;
+            match slots.get_mut(position) {
+                Some(slot @ None) => *slot = Some(message),
+                // Out of range, or this party has already submitted a message.
+                _ => return Err(MusigError::BadArguments),
+            }
+            if slots.iter().all(Option::is_some) {
+                let messages = slots.iter().flatten().cloned().collect();
+                self.sender
+                    .send(Some(messages))
+                    .map_err(|_| MusigError::BadArguments)?;
+            }
+        }
+
+        loop {
+            let messages = receiver.borrow().clone();
+            if let Some(messages) = messages {
+                return Ok(messages);
+            }
+            receiver
+                .changed()
+                .await
+                .map_err(|_| MusigError::BadArguments)?;
+        }
+    }
+}
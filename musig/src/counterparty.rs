@@ -1,33 +1,78 @@
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
-use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
-use starsig::{TranscriptProtocol, VerificationKey};
+use starsig::{TranscriptProtocol, VerificationKey, LABEL_KEY};
 use subtle::ConstantTimeEq;
 
-use super::{MusigContext, MusigError};
+use super::transcript::LABEL_KEY_COUNT;
+use super::{MusigContext, MusigError, SigningError};
 
-#[derive(Copy, Clone)]
+/// Precommitment to a party's nonce: `H(R_i)`, sent in the first round.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct NoncePrecommitment([u8; 32]);
 
-#[derive(Copy, Clone, Debug)]
-pub struct NonceCommitment(RistrettoPoint);
+/// Commitment to a party's nonce: `R_i`, sent in the second round.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NonceCommitment(CompressedRistretto);
+
+impl NoncePrecommitment {
+    /// Wraps the precommitment bytes received from another party.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        NoncePrecommitment(bytes)
+    }
+
+    /// Returns the byte representation of the precommitment.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
 
 impl NonceCommitment {
     pub(super) fn new(commitment: RistrettoPoint) -> Self {
-        NonceCommitment(commitment)
+        NonceCommitment(commitment.compress())
+    }
+
+    /// Wraps the compressed nonce point received from another party.
+    /// The point is checked for validity when the commitments are received by the signer.
+    pub fn from_point(point: CompressedRistretto) -> Self {
+        NonceCommitment(point)
+    }
+
+    /// Returns the compressed nonce point.
+    pub fn into_point(self) -> CompressedRistretto {
+        self.0
     }
 
-    pub(super) fn precommit(&self) -> NoncePrecommitment {
+    /// Makes the dedicated transcript that binds nonce precommitments to the
+    /// ordered list of public keys in the signing session.
+    pub(super) fn precommitment_transcript<C: MusigContext + ?Sized>(context: &C) -> Transcript {
         let mut h = Transcript::new(b"Musig.nonce-precommit");
-        h.append_point(b"R", &self.0.compress());
+        h.append_u64(LABEL_KEY_COUNT, context.len() as u64);
+        for i in 0..context.len() {
+            h.append_point(LABEL_KEY, context.key(i).as_point());
+        }
+        h
+    }
+
+    /// Makes H(<L>, R_i) using the transcript from `precommitment_transcript`,
+    /// so that the precommitment cannot collide with other hashes computed
+    /// in a composed protocol, and parties that disagree on the keys
+    /// fail to open each other's precommitments.
+    pub(super) fn precommit(&self, session: &Transcript) -> NoncePrecommitment {
+        let mut h = session.clone();
+        h.append_point(b"R", &self.0);
         let mut precommitment = [0u8; 32];
         h.challenge_bytes(b"precommitment", &mut precommitment);
         NoncePrecommitment(precommitment)
     }
 
-    pub(super) fn sum(commitments: &Vec<Self>) -> RistrettoPoint {
-        commitments.iter().map(|R_i| R_i.0).sum()
+    pub(super) fn decompress(&self) -> Result<RistrettoPoint, MusigError> {
+        self.0.decompress().ok_or(MusigError::InvalidPoint)
+    }
+
+    pub(super) fn sum(commitments: &[Self]) -> Result<RistrettoPoint, MusigError> {
+        commitments.iter().map(|R_i| R_i.decompress()).sum()
     }
 }
 
@@ -69,12 +114,13 @@ impl CounterpartyPrecommitted {
     pub(super) fn verify_nonce(
         self,
         commitment: NonceCommitment,
-    ) -> Result<CounterpartyCommitted, MusigError> {
-        // Check H(commitment) =? precommitment
-        let received_precommitment = commitment.precommit();
+        session: &Transcript,
+    ) -> Result<CounterpartyCommitted, SigningError> {
+        // Check H(<L>, commitment) =? precommitment
+        let received_precommitment = commitment.precommit(session);
         let equal = self.precommitment.0.ct_eq(&received_precommitment.0);
         if equal.unwrap_u8() == 0 {
-            return Err(MusigError::ShareError {
+            return Err(SigningError::Equivocation {
                 pubkey: self.pubkey.to_bytes(),
             });
         }
@@ -93,22 +139,41 @@ impl CounterpartyCommitted {
         share: Scalar,
         context: &C,
         transcript: &Transcript,
-    ) -> Result<Scalar, MusigError> {
-        // Check the partial Schnorr signature:
-        // s_i * G == R_i + c_i * X_i.
-        let S_i = share * RISTRETTO_BASEPOINT_POINT;
-        let c_i = context.challenge(self.position, &mut transcript.clone());
-        let X_i_compressed = self.pubkey.into_point();
-        let X_i = X_i_compressed
-            .decompress()
-            .ok_or(MusigError::InvalidPoint)?;
-
-        if S_i != self.commitment.0 + c_i * X_i {
-            return Err(MusigError::ShareError {
-                pubkey: X_i_compressed.to_bytes(),
-            });
-        }
+    ) -> Result<Scalar, SigningError> {
+        protocol_span!("musig.verify_share", self.position, transcript);
 
+        let c_i = context.challenge(self.position, &mut transcript.clone());
+        verify_share(share, self.commitment, c_i, self.pubkey).map_err(SigningError::from_share)?;
         Ok(share)
     }
 }
+
+/// Verifies a partial signature `s_i` of a single party against its nonce commitment `R_i`,
+/// the party's challenge `c_i` and its verification key `X_i`.
+/// The challenge is computed with `MusigContext::challenge` for the party's position and
+/// already includes the delinearization coefficient, if the context uses one.
+///
+/// The check requires no secrets, so a coordinator that is not a signer
+/// can use it to reject an invalid share before combining the shares into a signature.
+pub fn verify_share(
+    share: Scalar,
+    commitment: NonceCommitment,
+    challenge: Scalar,
+    pubkey: VerificationKey,
+) -> Result<(), MusigError> {
+    // Check the partial Schnorr signature:
+    // s_i * G == R_i + c_i * X_i.
+    let S_i = share * RISTRETTO_BASEPOINT_POINT;
+    let X_i_compressed = pubkey.into_point();
+    let X_i = X_i_compressed
+        .decompress()
+        .ok_or(MusigError::InvalidPoint)?;
+
+    if S_i != commitment.decompress()? + challenge * X_i {
+        return Err(MusigError::ShareError {
+            pubkey: X_i_compressed.to_bytes(),
+        });
+    }
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use starsig::StarsigError;
 use thiserror::Error;
 
 /// Represents an error in key aggregation, signing, or verification.
@@ -7,6 +8,13 @@ pub enum MusigError {
     #[error("Point decoding failed")]
     InvalidPoint,
 
+    /// This error occurs when a participant's key is not a valid compressed Ristretto point.
+    #[error("Key #{index} is not a valid point")]
+    InvalidKey {
+        /// The index of the invalid key in the list of keys
+        index: usize,
+    },
+
     /// This error occurs when a signature share fails to verify
     #[error("Share #{pubkey:?} failed to verify correctly")]
     ShareError {
@@ -14,6 +22,30 @@ pub enum MusigError {
         pubkey: [u8; 32],
     },
 
+    /// This error occurs when the same pubkey is listed more than once in a multikey.
+    #[error("Duplicate key {pubkey:?}")]
+    DuplicateKey {
+        /// The pubkey that occurs more than once
+        pubkey: [u8; 32],
+    },
+
+    /// This error occurs when a nonce precommitment is not included in the precommitment tree.
+    #[error("Precommitment is not included in the tree")]
+    PrecommitmentNotIncluded,
+
+    /// This error occurs when the summed weight of the signing parties is below the quorum threshold.
+    #[error("Signers' weight {weight} is below the threshold {threshold}")]
+    QuorumNotMet {
+        /// The summed weight of the signing parties
+        weight: u64,
+        /// The required weight
+        threshold: u64,
+    },
+
+    /// This error occurs when a claimed aggregated key does not match the participants' keys.
+    #[error("Aggregated key does not match the keys")]
+    AggregatedKeyMismatch,
+
     /// This error occurs when an individual point operation failed.
     #[error("Point operation failed")]
     PointOperationFailed,
@@ -21,4 +53,89 @@ pub enum MusigError {
     /// This error occurs when a function is called with bad arguments.
     #[error("Bad arguments")]
     BadArguments,
+
+    /// This error occurs when encoded data is malformed.
+    #[error("Format is invalid")]
+    InvalidFormat,
+
+    /// This error occurs when encoded data has an unknown version.
+    #[error("Unsupported encoding version {0}")]
+    UnsupportedVersion(u8),
+
+    /// This error occurs when a signature fails to verify.
+    /// The underlying cause is available via `Error::source`.
+    #[error("Signature verification failed")]
+    SignatureError(#[from] StarsigError),
+
+    /// This error occurs when a round of the multi-party signing protocol fails.
+    /// The underlying cause is available via `Error::source`.
+    #[error("Signing protocol failed")]
+    ProtocolError(#[from] SigningError),
+}
+
+/// Represents a failure of the multi-party signing protocol, as run by `Signer`, `SignerN`
+/// and `BatchSigner`.
+/// Converts into `MusigError::ProtocolError`.
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum SigningError {
+    /// This error occurs when a round does not receive exactly one message from each participant.
+    #[error("Expected messages from {expected} participants, received {received}")]
+    MissingParticipants {
+        /// The number of participants in the session
+        expected: usize,
+        /// The number of participants the messages were received from
+        received: usize,
+    },
+
+    /// This error occurs when a participant's signature share fails to verify
+    #[error("Share of {pubkey:?} does not match its nonce commitment and key")]
+    ShareMismatch {
+        /// The pubkey of the participant whose share failed to verify
+        pubkey: [u8; 32],
+    },
+
+    /// This error occurs when a participant's nonce commitment does not open its precommitment
+    #[error("Participant {pubkey:?} equivocated on its nonce")]
+    Equivocation {
+        /// The pubkey of the participant whose commitment does not match
+        pubkey: [u8; 32],
+    },
+
+    /// This error occurs when a participant's message is malformed (e.g. not a valid point),
+    /// so the session cannot continue.
+    #[error("Signing session aborted due to a malformed message")]
+    Aborted,
+
+    /// This error occurs when a participant of a batch session does not send
+    /// exactly one message per signed message.
+    #[error("Expected {expected} messages in the batch, received {received}")]
+    BatchSizeMismatch {
+        /// The number of signed messages in the session
+        expected: usize,
+        /// The number of messages received from the participant
+        received: usize,
+    },
+
+    /// This error occurs when the same nonce commitment is used more than once in a session.
+    #[error("Nonce commitment is reused")]
+    NonceReuse,
+}
+
+impl SigningError {
+    /// Converts the failure of `verify_share` for the signer path.
+    pub(crate) fn from_share(err: MusigError) -> Self {
+        match err {
+            MusigError::ShareError { pubkey } => SigningError::ShareMismatch { pubkey },
+            _ => SigningError::Aborted,
+        }
+    }
+
+    /// Checks that a round received one message from each of the `expected` participants.
+    pub(crate) fn check_count(expected: usize, received: usize) -> Result<(), Self> {
+        if expected == received {
+            Ok(())
+        } else {
+            Err(SigningError::MissingParticipants { expected, received })
+        }
+    }
 }
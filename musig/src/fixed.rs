@@ -0,0 +1,253 @@
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use starsig::{
+    Signature, TranscriptProtocol as StarsigTranscriptProtocol, VerificationKey, LABEL_CHALLENGE,
+    LABEL_KEY, LABEL_NONCE,
+};
+
+use super::counterparty::{verify_share, Counterparty, NonceCommitment, NoncePrecommitment};
+use super::{Multikey, MusigContext, MusigError, SigningError};
+
+/// MuSig aggregated key context for a fixed number of parties `N`.
+/// Unlike `Multikey`, it keeps the keys in an array and does not allocate,
+/// while producing the same aggregated key for the same list of keys.
+#[derive(Clone)]
+pub struct MultikeyN<const N: usize> {
+    /// Per-key factors `a_i`, derived once when the keys are aggregated.
+    factors: [Scalar; N],
+    aggregated_key: VerificationKey,
+    public_keys: [VerificationKey; N],
+}
+
+/// Entry point to multi-party signing protocol with a fixed number of parties.
+/// All the signer states are kept on the stack.
+pub struct SignerN {}
+
+/// State of the party when awaiting nonce precommitments from other parties.
+pub struct SignerAwaitingPrecommitmentsN<'t, const N: usize> {
+    transcript: &'t mut Transcript,
+    context: MultikeyN<N>,
+    position: usize,
+    x_i: Scalar,
+    r_i: Scalar,
+    R_i: NonceCommitment,
+}
+
+/// State of the party when awaiting nonce commitments from other parties.
+pub struct SignerAwaitingCommitmentsN<'t, const N: usize> {
+    transcript: &'t mut Transcript,
+    context: MultikeyN<N>,
+    position: usize,
+    x_i: Scalar,
+    r_i: Scalar,
+    precommitments: [NoncePrecommitment; N],
+}
+
+/// State of the party when awaiting signature shares from other parties.
+pub struct SignerAwaitingSharesN<const N: usize> {
+    transcript: Transcript,
+    context: MultikeyN<N>,
+    position: usize,
+    R: RistrettoPoint,
+    commitments: [NonceCommitment; N],
+}
+
+impl<const N: usize> MultikeyN<N> {
+    /// Constructs a new MuSig multikey aggregating the pubkeys.
+    /// The keys are validated like in `Multikey::new`, failing with the same errors.
+    pub fn new(pubkeys: [VerificationKey; N]) -> Result<Self, MusigError> {
+        match N {
+            0 => {
+                return Err(MusigError::BadArguments);
+            }
+            1 => {
+                Multikey::decompress_key(0, &pubkeys[0])?;
+                // Special case: single key can be wrapped in a Multikey type
+                // without a delinearization factor applied.
+                return Ok(MultikeyN {
+                    factors: [Scalar::one(); N],
+                    aggregated_key: pubkeys[0],
+                    public_keys: pubkeys,
+                });
+            }
+            _ => {}
+        }
+
+        Multikey::check_duplicate_bytes(&mut pubkeys.map(|X| X.to_bytes()))?;
+        let mut points = [RistrettoPoint::default(); N];
+        for (i, X) in pubkeys.iter().enumerate() {
+            points[i] = Multikey::decompress_key(i, X)?;
+        }
+        let prf = Multikey::transcript_for_keys(&pubkeys);
+        let mut factors = [Scalar::zero(); N];
+        Multikey::compute_factors(&prf, None, &mut factors);
+
+        // aggregated_key = sum_i ( a_i * X_i ), summed in a loop,
+        // since the multiscalar multiplication allocates.
+        let aggregated_key: RistrettoPoint = factors.iter().zip(&points).map(|(a, X)| a * X).sum();
+
+        Ok(MultikeyN {
+            factors,
+            aggregated_key: VerificationKey::from(aggregated_key),
+            public_keys: pubkeys,
+        })
+    }
+
+    /// Returns VerificationKey representation of aggregated key.
+    pub fn aggregated_key(&self) -> VerificationKey {
+        self.aggregated_key
+    }
+}
+
+impl<const N: usize> MusigContext for MultikeyN<N> {
+    fn commit(&self, transcript: &mut Transcript) {
+        transcript.starsig_domain_sep();
+        transcript.append_point(LABEL_KEY, self.aggregated_key.as_point());
+    }
+
+    fn challenge(&self, i: usize, transcript: &mut Transcript) -> Scalar {
+        // Make c = H(X, R, m) and a_i = H(<L>, i), as in `Multikey`.
+        let c = transcript.challenge_scalar(LABEL_CHALLENGE);
+        c * self.factors[i]
+    }
+
+    fn len(&self) -> usize {
+        N
+    }
+
+    fn key(&self, index: usize) -> VerificationKey {
+        self.public_keys[index]
+    }
+}
+
+impl SignerN {
+    /// Create new signing party for a given transcript.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<'t, const N: usize>(
+        // The message `m` has already been fed into the transcript
+        transcript: &'t mut Transcript,
+        position: usize,
+        x_i: Scalar,
+        context: MultikeyN<N>,
+    ) -> (SignerAwaitingPrecommitmentsN<'t, N>, NoncePrecommitment) {
+        protocol_span!("musig.new", position, transcript);
+
+        let mut rng = transcript
+            .build_rng()
+            .rekey_with_witness_bytes(b"x_i", &x_i.to_bytes())
+            .finalize(&mut rand::thread_rng());
+
+        // Generate ephemeral keypair (r_i, R_i). r_i is a random nonce.
+        let r_i = Scalar::random(&mut rng);
+        // R_i = generator * r_i
+        let R_i = NonceCommitment::new(RISTRETTO_BASEPOINT_POINT * r_i);
+        // Make H(<L>, R_i), binding the precommitment to the ordered key list
+        let session = NonceCommitment::precommitment_transcript(&context);
+        let precommitment = R_i.precommit(&session);
+
+        (
+            SignerAwaitingPrecommitmentsN {
+                transcript,
+                context,
+                position,
+                x_i,
+                r_i,
+                R_i,
+            },
+            precommitment,
+        )
+    }
+}
+
+impl<'t, const N: usize> SignerAwaitingPrecommitmentsN<'t, N> {
+    /// Provide nonce precommitments to the party and transition to the next round.
+    pub fn receive_precommitments(
+        self,
+        nonce_precommitments: &[NoncePrecommitment; N],
+    ) -> (SignerAwaitingCommitmentsN<'t, N>, NonceCommitment) {
+        protocol_span!(
+            "musig.receive_precommitments",
+            self.position,
+            self.transcript
+        );
+
+        (
+            SignerAwaitingCommitmentsN {
+                transcript: self.transcript,
+                context: self.context,
+                position: self.position,
+                x_i: self.x_i,
+                r_i: self.r_i,
+                precommitments: *nonce_precommitments,
+            },
+            self.R_i,
+        )
+    }
+}
+
+impl<'t, const N: usize> SignerAwaitingCommitmentsN<'t, N> {
+    /// Provide nonce commitments to the party and transition to the next round
+    /// if they match the precommitments.
+    pub fn receive_commitments(
+        self,
+        nonce_commitments: &[NonceCommitment; N],
+    ) -> Result<(SignerAwaitingSharesN<N>, Scalar), SigningError> {
+        protocol_span!("musig.receive_commitments", self.position, self.transcript);
+
+        // Make R = sum_i(R_i). nonce_commitments = R_i from all the parties.
+        let R = NonceCommitment::sum(nonce_commitments).map_err(|_| SigningError::Aborted)?;
+
+        // Check stored precommitments against received commitments
+        let session = NonceCommitment::precommitment_transcript(&self.context);
+        let pairs = self.precommitments.iter().zip(nonce_commitments);
+        for (i, (precommitment, commitment)) in pairs.enumerate() {
+            Counterparty::new(i, self.context.key(i))
+                .precommit_nonce(*precommitment)
+                .verify_nonce(*commitment, &session)?;
+        }
+
+        // Commit the context with label "X", and commit the nonce sum with label "R"
+        self.context.commit(self.transcript);
+        self.transcript.append_point(LABEL_NONCE, &R.compress());
+
+        // Keep a copy of the transcript for checking the shares of other parties.
+        let transcript = self.transcript.clone();
+
+        // Generate share: s_i = r_i + c * a_i * x_i
+        let c_i = self.context.challenge(self.position, self.transcript);
+        let s_i = self.r_i + c_i * self.x_i;
+
+        Ok((
+            SignerAwaitingSharesN {
+                transcript,
+                context: self.context,
+                position: self.position,
+                R,
+                commitments: *nonce_commitments,
+            },
+            s_i,
+        ))
+    }
+}
+
+impl<const N: usize> SignerAwaitingSharesN<N> {
+    /// Verify and assemble signature shares.
+    pub fn receive_shares(self, shares: &[Scalar; N]) -> Result<Signature, SigningError> {
+        protocol_span!("musig.receive_shares", self.position, &self.transcript);
+
+        let pairs = shares.iter().zip(&self.commitments);
+        for (i, (share, commitment)) in pairs.enumerate() {
+            let c_i = self.context.challenge(i, &mut self.transcript.clone());
+            verify_share(*share, *commitment, c_i, self.context.key(i))
+                .map_err(SigningError::from_share)?;
+        }
+
+        Ok(Signature {
+            s: shares.iter().sum(),
+            R: self.R.compress(),
+        })
+    }
+}
@@ -0,0 +1,350 @@
+//! Threshold signatures with the FROST protocol, as an alternative to MuSig
+//! when any `t` of the `n` key holders should be able to sign.
+//!
+//! This implements the FROST(ristretto255, SHA-512) ciphersuite of RFC 9591,
+//! so the signatures and signature shares interoperate with other implementations of it.
+//!
+//! Each signer commits to a pair of nonces `(d_i, e_i)` as `(D_i, E_i)`.
+//! The binding factor `rho_i = H1(X || H4(m) || H5(B) || i)` binds the nonces of each signer
+//! to the message and to the list `B` of all commitments, the group commitment is
+//! `R = sum_i(D_i + rho_i * E_i)` and the challenge is `c = H2(R || X || m)`.
+//! Signer `i` with the secret share `s_i` and the Lagrange coefficient `lambda_i` makes
+//! the signature share `z_i = d_i + e_i * rho_i + lambda_i * s_i * c`,
+//! and the aggregated signature `(R, sum_i z_i)` verifies with `frost::verify` against the group key.
+//! Since the challenge is not the Starsig challenge, it does not verify with `Signature::verify`.
+
+use std::collections::BTreeSet;
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use rand::{CryptoRng, RngCore};
+
+use starsig::{Signature, StarsigError, VerificationKey};
+
+use super::sha512::Sha512;
+use super::MusigError;
+
+/// Context string of the FROST(ristretto255, SHA-512) ciphersuite, prefixed to all the hashes.
+const CONTEXT_STRING: &[u8] = b"FROST-RISTRETTO255-SHA512-v1";
+
+/// Secret share `s_i = f(i)` of the group secret held by the signer with a nonzero `identifier`.
+#[derive(Clone)]
+pub struct SecretShare {
+    identifier: u64,
+    secret: Scalar,
+}
+
+/// Nonces `(d_i, e_i)` of a signer for a single signing session.
+/// They must be used for at most one signature share.
+pub struct SigningNonces {
+    hiding: Scalar,
+    binding: Scalar,
+}
+
+/// Commitment `(D_i, E_i)` to the nonces of the signer with the given `identifier`,
+/// sent to the other signers in the first round.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SigningCommitment {
+    /// Identifier of the signer
+    pub identifier: u64,
+    /// Hiding nonce commitment `D_i = d_i * G`
+    pub hiding: CompressedRistretto,
+    /// Binding nonce commitment `E_i = e_i * G`
+    pub binding: CompressedRistretto,
+}
+
+/// Message and nonce commitments of the signers taking part in a signing session.
+pub struct SigningPackage {
+    message: Vec<u8>,
+    group_key: VerificationKey,
+    commitments: Vec<SigningCommitment>,
+}
+
+/// Splits the `secret` into `max_signers` shares, any `min_signers` of which can sign,
+/// with Shamir's secret sharing by a trusted dealer. The shares have the identifiers
+/// `1..=max_signers`. Returns the shares and the group key `secret * G`.
+pub fn trusted_dealer_keygen<R: RngCore + CryptoRng>(
+    secret: Scalar,
+    min_signers: usize,
+    max_signers: usize,
+    rng: &mut R,
+) -> Result<(Vec<SecretShare>, VerificationKey), MusigError> {
+    if min_signers == 0 || min_signers > max_signers {
+        return Err(MusigError::BadArguments);
+    }
+    // f(x) = secret + a_1 * x + ... + a_{t-1} * x^{t-1}
+    let coefficients: Vec<Scalar> = std::iter::once(secret)
+        .chain((1..min_signers).map(|_| Scalar::random(rng)))
+        .collect();
+    let shares = (1..=max_signers as u64)
+        .map(|identifier| {
+            let x = Scalar::from(identifier);
+            // Horner's rule, starting from the highest coefficient.
+            let secret = coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::zero(), |acc, a| acc * x + a);
+            SecretShare { identifier, secret }
+        })
+        .collect();
+    Ok((shares, VerificationKey::from_secret(&secret)))
+}
+
+/// Computes the Lagrange coefficient `lambda_i` of the signer with the `identifier`,
+/// for interpolating the secret at zero from the shares of the signers with the `identifiers`.
+/// Fails with `MusigError::BadArguments` if an identifier is zero or repeated,
+/// or if the `identifier` is not one of the `identifiers`,
+/// since the coefficient is then undefined.
+pub fn lagrange_coefficient(identifier: u64, identifiers: &[u64]) -> Result<Scalar, MusigError> {
+    let mut seen = BTreeSet::new();
+    if identifiers.iter().any(|j| *j == 0 || !seen.insert(*j)) || !seen.contains(&identifier) {
+        return Err(MusigError::BadArguments);
+    }
+    let x_i = Scalar::from(identifier);
+    let (num, den) = identifiers
+        .iter()
+        .filter(|j| **j != identifier)
+        .map(|j| Scalar::from(*j))
+        .fold((Scalar::one(), Scalar::one()), |(num, den), x_j| {
+            (num * x_j, den * (x_j - x_i))
+        });
+    Ok(num * den.invert())
+}
+
+/// Verifies the FROST signature over the `message` by the `group_key`:
+/// `z * G == R + c * X` with `c = H2(R || X || m)`.
+/// Like the RFC 9591 signature decoding, a non-canonical `z` or an identity `R` is rejected.
+pub fn verify(
+    message: &[u8],
+    group_key: VerificationKey,
+    signature: &Signature,
+) -> Result<(), MusigError> {
+    if !signature.s.is_canonical() {
+        return Err(StarsigError::InvalidSignature.into());
+    }
+    let R = decompress(&signature.R)?;
+    let X = decompress(group_key.as_point())?;
+    let c = challenge(&signature.R, &group_key, message);
+    if RISTRETTO_BASEPOINT_POINT * signature.s == R + X * c {
+        Ok(())
+    } else {
+        Err(StarsigError::InvalidSignature.into())
+    }
+}
+
+impl SecretShare {
+    /// Creates the share `secret` of the signer with the `identifier`,
+    /// e.g. one produced by another implementation of FROST.
+    /// Fails with `MusigError::BadArguments` if the identifier is zero.
+    pub fn new(identifier: u64, secret: Scalar) -> Result<Self, MusigError> {
+        if identifier == 0 {
+            return Err(MusigError::BadArguments);
+        }
+        Ok(SecretShare { identifier, secret })
+    }
+
+    /// Returns the identifier of the signer holding the share.
+    pub fn identifier(&self) -> u64 {
+        self.identifier
+    }
+
+    /// Returns the verification share `Y_i = s_i * G`, for checking the signer's signature shares.
+    pub fn verification_key(&self) -> VerificationKey {
+        VerificationKey::from_secret(&self.secret)
+    }
+
+    /// Makes the signature share `z_i = d_i + e_i * rho_i + lambda_i * s_i * c`,
+    /// consuming the `nonces` committed to in the `package`.
+    pub fn sign(
+        &self,
+        nonces: SigningNonces,
+        package: &SigningPackage,
+    ) -> Result<Scalar, MusigError> {
+        let own = nonces.commitment(self.identifier);
+        if !package.commitments.contains(&own) {
+            return Err(MusigError::BadArguments);
+        }
+        let R = package.group_commitment()?.compress();
+        let c = challenge(&R, &package.group_key, &package.message);
+        let rho_i = package.binding_factor(self.identifier);
+        let lambda_i = package.lagrange_coefficient(self.identifier)?;
+
+        Ok(nonces.hiding + nonces.binding * rho_i + lambda_i * self.secret * c)
+    }
+}
+
+impl SigningNonces {
+    /// Generates the nonces for the holder of the `share` from 32 random bytes each
+    /// and the secret share, so that a weak `rng` alone does not reveal the nonces.
+    pub fn new<R: RngCore + CryptoRng>(share: &SecretShare, rng: &mut R) -> Self {
+        let mut hiding_randomness = [0u8; 32];
+        let mut binding_randomness = [0u8; 32];
+        rng.fill_bytes(&mut hiding_randomness);
+        rng.fill_bytes(&mut binding_randomness);
+        Self::from_randomness(share, &hiding_randomness, &binding_randomness)
+    }
+
+    /// Derives the nonces `H3(randomness || s_i)` from the given randomness,
+    /// e.g. for reproducing the test vectors.
+    pub(crate) fn from_randomness(
+        share: &SecretShare,
+        hiding_randomness: &[u8; 32],
+        binding_randomness: &[u8; 32],
+    ) -> Self {
+        let secret = share.secret.to_bytes();
+        SigningNonces {
+            hiding: hash_to_scalar(b"nonce", &[hiding_randomness, &secret]),
+            binding: hash_to_scalar(b"nonce", &[binding_randomness, &secret]),
+        }
+    }
+
+    /// Returns the commitment to the nonces, to be sent to the other signers.
+    pub fn commitment(&self, identifier: u64) -> SigningCommitment {
+        SigningCommitment {
+            identifier,
+            hiding: (RISTRETTO_BASEPOINT_POINT * self.hiding).compress(),
+            binding: (RISTRETTO_BASEPOINT_POINT * self.binding).compress(),
+        }
+    }
+}
+
+impl SigningPackage {
+    /// Creates the package for signing the `message` under the `group_key`,
+    /// with the `commitments` of the signers.
+    /// The commitments are sorted by identifier, so all the signers agree on their order.
+    /// Fails with `MusigError::BadArguments` if there are no commitments, or if an identifier is zero or repeated.
+    pub fn new(
+        message: &[u8],
+        group_key: VerificationKey,
+        mut commitments: Vec<SigningCommitment>,
+    ) -> Result<Self, MusigError> {
+        commitments.sort_by_key(|commitment| commitment.identifier);
+        let mut seen = BTreeSet::new();
+        if commitments.is_empty()
+            || commitments
+                .iter()
+                .any(|commitment| commitment.identifier == 0 || !seen.insert(commitment.identifier))
+        {
+            return Err(MusigError::BadArguments);
+        }
+        Ok(SigningPackage {
+            message: message.to_vec(),
+            group_key,
+            commitments,
+        })
+    }
+
+    /// Verifies the signature `share` of the signer with the `identifier`
+    /// against its verification share `Y_i`: `z_i * G == D_i + rho_i * E_i + lambda_i * c * Y_i`.
+    pub fn verify_share(
+        &self,
+        identifier: u64,
+        share: Scalar,
+        verification_key: VerificationKey,
+    ) -> Result<(), MusigError> {
+        let commitment = self
+            .commitments
+            .iter()
+            .find(|commitment| commitment.identifier == identifier)
+            .ok_or(MusigError::BadArguments)?;
+        let R = self.group_commitment()?.compress();
+        let c = challenge(&R, &self.group_key, &self.message);
+        let rho_i = self.binding_factor(identifier);
+        let lambda_i = self.lagrange_coefficient(identifier)?;
+        let D_i = decompress(&commitment.hiding)?;
+        let E_i = decompress(&commitment.binding)?;
+        let Y_i = decompress(verification_key.as_point())?;
+
+        if RISTRETTO_BASEPOINT_POINT * share == D_i + E_i * rho_i + Y_i * (lambda_i * c) {
+            Ok(())
+        } else {
+            Err(MusigError::ShareError {
+                pubkey: verification_key.to_bytes(),
+            })
+        }
+    }
+
+    /// Aggregates the signature shares, in the order of the signers' identifiers,
+    /// into the signature `(R, sum_i z_i)` over the message by the group key.
+    /// Its encoding `R || z` is the RFC 9591 signature encoding.
+    pub fn aggregate(&self, shares: &[Scalar]) -> Result<Signature, MusigError> {
+        if shares.len() != self.commitments.len() {
+            return Err(MusigError::BadArguments);
+        }
+        Ok(Signature {
+            s: shares.iter().sum(),
+            R: self.group_commitment()?.compress(),
+        })
+    }
+
+    /// Makes the binding factor `rho_i = H1(X || H4(m) || H5(B) || i)`,
+    /// where `B` is the encoded list of all the commitments.
+    fn binding_factor(&self, identifier: u64) -> Scalar {
+        let mut encoded_commitments = Vec::with_capacity(self.commitments.len() * 96);
+        for commitment in &self.commitments {
+            encoded_commitments.extend_from_slice(Scalar::from(commitment.identifier).as_bytes());
+            encoded_commitments.extend_from_slice(commitment.hiding.as_bytes());
+            encoded_commitments.extend_from_slice(commitment.binding.as_bytes());
+        }
+        hash_to_scalar(
+            b"rho",
+            &[
+                self.group_key.as_bytes(),
+                &hash(b"msg", &[&self.message]),
+                &hash(b"com", &[&encoded_commitments]),
+                Scalar::from(identifier).as_bytes(),
+            ],
+        )
+    }
+
+    /// Makes the group commitment `R = sum_i(D_i + rho_i * E_i)`.
+    fn group_commitment(&self) -> Result<RistrettoPoint, MusigError> {
+        self.commitments
+            .iter()
+            .map(|commitment| {
+                let rho_i = self.binding_factor(commitment.identifier);
+                Ok(decompress(&commitment.hiding)? + decompress(&commitment.binding)? * rho_i)
+            })
+            .sum()
+    }
+
+    fn lagrange_coefficient(&self, identifier: u64) -> Result<Scalar, MusigError> {
+        let identifiers: Vec<u64> = self
+            .commitments
+            .iter()
+            .map(|commitment| commitment.identifier)
+            .collect();
+        lagrange_coefficient(identifier, &identifiers)
+    }
+}
+
+/// Makes the challenge `c = H2(R || X || m)`.
+fn challenge(R: &CompressedRistretto, group_key: &VerificationKey, message: &[u8]) -> Scalar {
+    hash_to_scalar(b"chal", &[R.as_bytes(), group_key.as_bytes(), message])
+}
+
+/// Computes `SHA-512(contextString || tag || inputs)`.
+fn hash(tag: &[u8], inputs: &[&[u8]]) -> [u8; 64] {
+    let mut hasher = Sha512::new();
+    hasher.update(CONTEXT_STRING);
+    hasher.update(tag);
+    for input in inputs {
+        hasher.update(input);
+    }
+    hasher.finalize()
+}
+
+/// Maps the hash of the inputs to a scalar, as the ristretto255 `hash_to_scalar`.
+fn hash_to_scalar(tag: &[u8], inputs: &[&[u8]]) -> Scalar {
+    Scalar::from_bytes_mod_order_wide(&hash(tag, inputs))
+}
+
+/// Decodes a point, rejecting the identity like the ristretto255 `DeserializeElement`.
+fn decompress(point: &CompressedRistretto) -> Result<RistrettoPoint, MusigError> {
+    point
+        .decompress()
+        .filter(|point| !point.is_identity())
+        .ok_or(MusigError::InvalidPoint)
+}
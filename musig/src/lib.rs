@@ -2,10 +2,29 @@
 #![allow(non_snake_case)]
 //! Musig implementation
 
+#[macro_use]
+mod trace;
+
+mod accumulator;
+mod aggregation_proof;
+mod batch_party;
+mod batch_signer;
+mod codec;
 mod context;
+#[cfg(feature = "async")]
+mod coordinator;
 mod counterparty;
+mod fixed;
+#[cfg(feature = "frost")]
+pub mod frost;
 mod multisignature;
+mod precommitment_tree;
+mod serialization;
+#[cfg(feature = "frost")]
+mod sha512;
+mod shared_key;
 mod signer;
+mod tx_signer;
 
 mod errors;
 mod transcript;
@@ -17,12 +36,36 @@ mod tests;
 pub use starsig::TranscriptProtocol as StarsigTranscriptProtocol;
 pub use starsig::{
     BatchVerification, BatchVerifier, Signature, SingleVerifier, StarsigError, VerificationKey,
+    LABEL_CHALLENGE, LABEL_KEY, LABEL_NONCE,
 };
 
-pub use self::context::{Multikey, Multimessage, MusigContext};
-pub use self::errors::MusigError;
+pub use self::accumulator::MultikeyAccumulator;
+pub use self::aggregation_proof::AggregationProof;
+pub use self::batch_party::BatchParty;
+pub use self::batch_signer::{
+    BatchSigner, BatchSignerAwaitingCommitments, BatchSignerAwaitingPrecommitments,
+    BatchSignerAwaitingShares,
+};
+pub use self::codec::{SignerCodec, SignerMessage, SignerState};
+pub use self::context::{
+    aggregate_keys, aggregate_keys_bytes, aggregate_many, Multikey, Multimessage, MusigContext,
+};
+#[cfg(feature = "async")]
+pub use self::coordinator::AsyncCoordinator;
+pub use self::counterparty::{verify_share, NonceCommitment, NoncePrecommitment};
+pub use self::errors::{MusigError, SigningError};
+pub use self::fixed::{
+    MultikeyN, SignerAwaitingCommitmentsN, SignerAwaitingPrecommitmentsN, SignerAwaitingSharesN,
+    SignerN,
+};
 pub use self::multisignature::Multisignature;
+pub use self::precommitment_tree::PrecommitmentTree;
+pub use self::shared_key::{split_secret, SharedKey};
 pub use self::signer::{
-    Signer, SignerAwaitingCommitments, SignerAwaitingPrecommitments, SignerAwaitingShares,
+    sign_aggregate, Signer, SignerAwaitingCommitments, SignerAwaitingPrecommitments,
+    SignerAwaitingShares,
+};
+pub use self::transcript::{
+    TranscriptProtocol, LABEL_AGGREGATION, LABEL_COEFFICIENT, LABEL_INDEX, LABEL_KEY_COUNT,
 };
-pub use self::transcript::TranscriptProtocol;
+pub use self::tx_signer::TxSigner;
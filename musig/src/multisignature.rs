@@ -4,7 +4,8 @@ use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 use starsig::{
-    BatchVerification, Signature, SingleVerifier, StarsigError, TranscriptProtocol, VerificationKey,
+    BatchVerification, Signature, SingleVerifier, StarsigError, TranscriptProtocol,
+    VerificationKey, LABEL_NONCE,
 };
 
 use super::{Multimessage, MusigContext, MusigError};
@@ -76,7 +77,7 @@ impl Multisignature for Signature {
 
         // Commit the context, and commit the nonce sum with label "R"
         context.commit(transcript);
-        transcript.append_point(b"R", &R);
+        transcript.append_point(LABEL_NONCE, &R);
 
         // Generate signature: s = r + sum{c_i * x_i}
         let mut s = r;
@@ -95,6 +96,7 @@ impl Multisignature for Signature {
         transcript: &mut Transcript,
         messages: Vec<(VerificationKey, M)>,
     ) -> Result<(), StarsigError> {
+        protocol_span!("musig.verify_multi", transcript);
         SingleVerifier::verify(|verifier| self.verify_multi_batched(transcript, messages, verifier))
     }
 
@@ -106,7 +108,7 @@ impl Multisignature for Signature {
     ) {
         let context = Multimessage::new(messages);
         context.commit(transcript);
-        transcript.append_point(b"R", &self.R);
+        transcript.append_point(LABEL_NONCE, &self.R);
 
         // Form the final linear combination:
         // `s * G = R + sum{c_i * X_i}`
@@ -0,0 +1,80 @@
+use merkle::{Hash, Hasher, MerkleItem, MerkleTree, Path};
+use merlin::Transcript;
+
+use super::{MusigError, NoncePrecommitment};
+
+/// Merkle commitment over the nonce precommitments of all the parties.
+/// Instead of broadcasting every precommitment to every party, a coordinator collects them
+/// and sends each party the root and the party's own merkle path.
+/// Each party checks the inclusion of its precommitment with `verify_inclusion`
+/// before revealing its nonce commitment `R_i`.
+pub struct PrecommitmentTree {
+    root: Hash,
+    leaves: Vec<PrecommitmentLeaf>,
+}
+
+/// Precommitment of the party at a given position.
+/// The position is committed together with the precommitment,
+/// so a path proves the inclusion of the precommitment at that exact position.
+#[derive(Copy, Clone)]
+struct PrecommitmentLeaf {
+    position: usize,
+    precommitment: NoncePrecommitment,
+}
+
+impl MerkleItem for PrecommitmentLeaf {
+    fn commit(&self, t: &mut Transcript) {
+        t.append_u64(b"i", self.position as u64);
+        t.append_message(b"precommitment", &self.precommitment.to_bytes());
+    }
+}
+
+impl PrecommitmentTree {
+    /// Builds the tree over the precommitments of all the parties, ordered by position.
+    pub fn new(precommitments: &[NoncePrecommitment]) -> Self {
+        let leaves: Vec<_> = precommitments
+            .iter()
+            .enumerate()
+            .map(|(position, precommitment)| PrecommitmentLeaf {
+                position,
+                precommitment: *precommitment,
+            })
+            .collect();
+        let root = MerkleTree::root(Self::LABEL, leaves.iter().copied());
+        PrecommitmentTree { root, leaves }
+    }
+
+    /// Returns the root hash, committing to all the precommitments.
+    pub fn root(&self) -> Hash {
+        self.root
+    }
+
+    /// Returns the merkle path for the precommitment of the party at the given position.
+    pub fn path(&self, position: usize) -> Option<Path> {
+        if position >= self.leaves.len() {
+            return None;
+        }
+        Path::new(&self.leaves, position, &Hasher::new(Self::LABEL))
+    }
+
+    /// Checks that the `precommitment` of the party at the given `position`
+    /// is included in the tree with the given `root`.
+    pub fn verify_inclusion(
+        root: &Hash,
+        position: usize,
+        precommitment: NoncePrecommitment,
+        path: &Path,
+    ) -> Result<(), MusigError> {
+        let leaf = PrecommitmentLeaf {
+            position,
+            precommitment,
+        };
+        if path.verify_root(root, &leaf, &Hasher::new(Self::LABEL)) {
+            Ok(())
+        } else {
+            Err(MusigError::PrecommitmentNotIncluded)
+        }
+    }
+
+    const LABEL: &'static [u8] = b"Musig.precommitments";
+}
@@ -0,0 +1,86 @@
+use curve25519_dalek::ristretto::CompressedRistretto;
+use readerwriter::{ReadError, Reader, WriteError, Writer};
+use starsig::VerificationKey;
+
+use super::{Multikey, MusigContext, MusigError};
+
+/// Current version of the `Multikey` encoding.
+const MULTIKEY_VERSION: u8 = 1;
+
+/// Version of the encoding of a `Multikey` created with `new_weighted`.
+const WEIGHTED_MULTIKEY_VERSION: u8 = 2;
+
+impl Multikey {
+    /// Encodes the multikey for long-term storage.
+    /// The format is a 1-byte version, followed by LE32-encoded number of keys,
+    /// the keys in order, and the aggregated key.
+    /// Weighted multikeys use version 2, with each key followed by its LE64-encoded weight.
+    /// Labels of the keys (see `new_labeled`) are not encoded.
+    pub fn serialize_versioned(&self) -> Vec<u8> {
+        let weights_size = self.weights().map(|w| w.len() * 8).unwrap_or(0);
+        let mut buf =
+            Vec::with_capacity(1 + 4 + (self.len() + 1) * VerificationKey::SIZE + weights_size);
+        self.write_versioned(&mut buf)
+            .expect("Writing to a Vec never fails.");
+        buf
+    }
+
+    /// Decodes the multikey encoded with `serialize_versioned`.
+    /// Fails if the version is unknown, if any key is not a valid point,
+    /// or if the stored aggregated key does not match the keys.
+    pub fn deserialize_versioned(mut data: &[u8]) -> Result<Self, MusigError> {
+        data.read_all(|r| {
+            let version = r.read_u8()?;
+            let n = r.read_u32()? as usize;
+            let multikey = match version {
+                MULTIKEY_VERSION => Multikey::new(r.read_vec(n, read_key)?)?,
+                WEIGHTED_MULTIKEY_VERSION => Multikey::new_weighted(
+                    &r.read_vec(n, |r| Ok::<_, MusigError>((read_key(r)?, r.read_u64()?)))?,
+                )?,
+                _ => return Err(MusigError::UnsupportedVersion(version)),
+            };
+            let aggregated_key = read_key(r)?;
+
+            if multikey.aggregated_key() != aggregated_key {
+                return Err(MusigError::InvalidFormat);
+            }
+            Ok(multikey)
+        })
+    }
+
+    fn write_versioned(&self, w: &mut impl Writer) -> Result<(), WriteError> {
+        match self.weights() {
+            None => {
+                w.write_u8(b"version", MULTIKEY_VERSION)?;
+                w.write_u32(b"n", self.len() as u32)?;
+                for i in 0..self.len() {
+                    w.write(b"X", self.key(i).as_bytes())?;
+                }
+            }
+            Some(weights) => {
+                w.write_u8(b"version", WEIGHTED_MULTIKEY_VERSION)?;
+                w.write_u32(b"n", self.len() as u32)?;
+                for (i, weight) in weights.iter().enumerate() {
+                    w.write(b"X", self.key(i).as_bytes())?;
+                    w.write_u64(b"w", *weight)?;
+                }
+            }
+        }
+        w.write(b"X", self.aggregated_key().as_bytes())
+    }
+}
+
+pub(super) fn read_key(r: &mut impl Reader) -> Result<VerificationKey, MusigError> {
+    let key = VerificationKey::from_compressed(CompressedRistretto(r.read_u8x32()?));
+    // Ristretto decompression rejects non-canonical encodings.
+    key.into_point()
+        .decompress()
+        .ok_or(MusigError::InvalidPoint)?;
+    Ok(key)
+}
+
+impl From<ReadError> for MusigError {
+    fn from(_: ReadError) -> Self {
+        MusigError::InvalidFormat
+    }
+}
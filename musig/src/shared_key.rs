@@ -0,0 +1,85 @@
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand::{CryptoRng, RngCore};
+
+use starsig::{TranscriptProtocol, VerificationKey, LABEL_CHALLENGE, LABEL_KEY};
+
+use super::{MusigContext, MusigError};
+
+/// Splits the `secret` into `n` random additive shares that sum to the secret,
+/// e.g. for a dealer that distributes a single key across the parties.
+/// The parties sign with the `SharedKey` context, and the signature
+/// verifies against `secret * G`.
+pub fn split_secret<R: RngCore + CryptoRng>(
+    secret: Scalar,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<Scalar>, MusigError> {
+    if n == 0 {
+        return Err(MusigError::BadArguments);
+    }
+    let mut shares: Vec<Scalar> = (1..n).map(|_| Scalar::random(rng)).collect();
+    let sum: Scalar = shares.iter().sum();
+    shares.push(secret - sum);
+    Ok(shares)
+}
+
+/// Context for the parties holding additive shares of a single key (see `split_secret`).
+/// The aggregated key is the plain sum of the parties' keys, without delinearization factors.
+///
+/// Since the keys are not delinearized, this context is only safe for the keys of shares
+/// produced by a trusted dealer: a party that chooses its own key can cancel out
+/// the other parties' keys. For distinct keys chosen by the parties use `Multikey`.
+#[derive(Clone)]
+pub struct SharedKey {
+    aggregated_key: VerificationKey,
+    public_keys: Vec<VerificationKey>,
+}
+
+impl SharedKey {
+    /// Constructs the context from the keys of the shares, ordered by the parties' positions.
+    pub fn new(pubkeys: Vec<VerificationKey>) -> Result<Self, MusigError> {
+        if pubkeys.is_empty() {
+            return Err(MusigError::BadArguments);
+        }
+        let aggregated_key = pubkeys
+            .iter()
+            .enumerate()
+            .map(|(index, X)| {
+                X.as_point()
+                    .decompress()
+                    .ok_or(MusigError::InvalidKey { index })
+            })
+            .sum::<Result<RistrettoPoint, _>>()?;
+        Ok(SharedKey {
+            aggregated_key: VerificationKey::from(aggregated_key),
+            public_keys: pubkeys,
+        })
+    }
+
+    /// Returns the shared key, equal to `secret * G` for the shares of the `secret`.
+    pub fn aggregated_key(&self) -> VerificationKey {
+        self.aggregated_key
+    }
+}
+
+impl MusigContext for SharedKey {
+    fn commit(&self, transcript: &mut Transcript) {
+        transcript.starsig_domain_sep();
+        transcript.append_point(LABEL_KEY, self.aggregated_key.as_point());
+    }
+
+    fn challenge(&self, _i: usize, transcript: &mut Transcript) -> Scalar {
+        // Make c = H(X, R, m). Each party's share is weighted by 1.
+        transcript.challenge_scalar(LABEL_CHALLENGE)
+    }
+
+    fn len(&self) -> usize {
+        self.public_keys.len()
+    }
+
+    fn key(&self, index: usize) -> VerificationKey {
+        self.public_keys[index]
+    }
+}
@@ -4,14 +4,29 @@ use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 use rand;
 
-use starsig::{Signature, TranscriptProtocol};
+use starsig::{Signature, TranscriptProtocol, VerificationKey, LABEL_NONCE};
 
 use super::counterparty::*;
-use super::{MusigContext, MusigError};
+use super::{Multikey, MusigContext, MusigError, SigningError};
 
 /// Entry point to multi-party signing protocol.
 pub struct Signer {}
 
+/// Signs the transcript with all the private keys at once, without running the multi-party protocol.
+/// This is useful in tests and in setups where a single party controls all the keys.
+/// Returns the multikey aggregating the corresponding pubkeys, and the signature
+/// that verifies against its aggregated key.
+pub fn sign_aggregate(
+    // The message `m` has already been fed into the transcript
+    transcript: &mut Transcript,
+    privkeys: &[Scalar],
+) -> Result<(Multikey, Signature), MusigError> {
+    let multikey = Multikey::new(privkeys.iter().map(VerificationKey::from_secret).collect())?;
+    let aggregated_privkey = Multikey::aggregated_signing_key(privkeys);
+    let signature = Signature::sign(transcript, aggregated_privkey);
+    Ok((multikey, signature))
+}
+
 /// State of the party when awaiting nonce precommitments from other parties.
 pub struct SignerAwaitingPrecommitments<'t, C: MusigContext> {
     transcript: &'t mut Transcript,
@@ -36,6 +51,7 @@ pub struct SignerAwaitingCommitments<'t, C: MusigContext> {
 /// State of the party when awaiting signature shares from other parties.
 pub struct SignerAwaitingShares<C: MusigContext> {
     transcript: Transcript,
+    position: usize,
     context: C,
     R: RistrettoPoint,
     counterparties: Vec<CounterpartyCommitted>,
@@ -50,6 +66,8 @@ impl Signer {
         x_i: Scalar,
         context: C,
     ) -> (SignerAwaitingPrecommitments<'t, C>, NoncePrecommitment) {
+        protocol_span!("musig.new", position, transcript);
+
         let mut rng = transcript
             .build_rng()
             .rekey_with_witness_bytes(b"x_i", &x_i.to_bytes())
@@ -59,8 +77,9 @@ impl Signer {
         let r_i = Scalar::random(&mut rng);
         // R_i = generator * r_i
         let R_i = NonceCommitment::new(RISTRETTO_BASEPOINT_POINT * r_i);
-        // Make H(R_i)
-        let precommitment = R_i.precommit();
+        // Make H(<L>, R_i), binding the precommitment to the ordered key list
+        let session = NonceCommitment::precommitment_transcript(&context);
+        let precommitment = R_i.precommit(&session);
 
         let counterparties = (0..context.len())
             .map(|i| Counterparty::new(i, context.key(i)))
@@ -83,18 +102,26 @@ impl Signer {
 
 impl<'t, C: MusigContext> SignerAwaitingPrecommitments<'t, C> {
     /// Provide nonce precommitments to the party and transition to the next round.
+    /// Fails with `SigningError::MissingParticipants` unless there is one precommitment per party.
     pub fn receive_precommitments(
         self,
-        nonce_precommitments: Vec<NoncePrecommitment>,
-    ) -> (SignerAwaitingCommitments<'t, C>, NonceCommitment) {
+        nonce_precommitments: &[NoncePrecommitment],
+    ) -> Result<(SignerAwaitingCommitments<'t, C>, NonceCommitment), SigningError> {
+        protocol_span!(
+            "musig.receive_precommitments",
+            self.position,
+            self.transcript
+        );
+        SigningError::check_count(self.counterparties.len(), nonce_precommitments.len())?;
+
         let counterparties = self
             .counterparties
             .into_iter()
-            .zip(nonce_precommitments)
+            .zip(nonce_precommitments.iter().copied())
             .map(|(counterparty, precommitment)| counterparty.precommit_nonce(precommitment))
             .collect();
         // Store received nonce precommitments in next state
-        (
+        Ok((
             SignerAwaitingCommitments {
                 transcript: self.transcript,
                 context: self.context,
@@ -104,31 +131,37 @@ impl<'t, C: MusigContext> SignerAwaitingPrecommitments<'t, C> {
                 counterparties,
             },
             self.R_i,
-        )
+        ))
     }
 }
 
 impl<'t, C: MusigContext> SignerAwaitingCommitments<'t, C> {
     /// Provide nonce commitments to the party and transition to the next round
     /// if they match the precommitments.
+    /// Fails with `SigningError::MissingParticipants` unless there is one commitment per party,
+    /// and with `SigningError::Equivocation` if a commitment does not match its precommitment.
     pub fn receive_commitments(
         mut self,
-        nonce_commitments: Vec<NonceCommitment>,
-    ) -> Result<(SignerAwaitingShares<C>, Scalar), MusigError> {
+        nonce_commitments: &[NonceCommitment],
+    ) -> Result<(SignerAwaitingShares<C>, Scalar), SigningError> {
+        protocol_span!("musig.receive_commitments", self.position, self.transcript);
+        SigningError::check_count(self.counterparties.len(), nonce_commitments.len())?;
+
         // Make R = sum_i(R_i). nonce_commitments = R_i from all the parties.
-        let R = NonceCommitment::sum(&nonce_commitments);
+        let R = NonceCommitment::sum(nonce_commitments).map_err(|_| SigningError::Aborted)?;
 
         // Check stored precommitments against received commitments
+        let session = NonceCommitment::precommitment_transcript(&self.context);
         let counterparties = self
             .counterparties
             .into_iter()
-            .zip(nonce_commitments)
-            .map(|(counterparty, commitment)| counterparty.verify_nonce(commitment))
+            .zip(nonce_commitments.iter().copied())
+            .map(|(counterparty, commitment)| counterparty.verify_nonce(commitment, &session))
             .collect::<Result<_, _>>()?;
 
         // Commit the context with label "X", and commit the nonce sum with label "R"
         self.context.commit(&mut self.transcript);
-        self.transcript.append_point(b"R", &R.compress());
+        self.transcript.append_point(LABEL_NONCE, &R.compress());
 
         // Make a copy of the transcript for extracting the challenge c_i.
         // This way, we can pass self.transcript to the next state so the next state
@@ -145,6 +178,7 @@ impl<'t, C: MusigContext> SignerAwaitingCommitments<'t, C> {
         Ok((
             SignerAwaitingShares {
                 transcript,
+                position: self.position,
                 context: self.context,
                 R,
                 counterparties,
@@ -156,17 +190,54 @@ impl<'t, C: MusigContext> SignerAwaitingCommitments<'t, C> {
 
 impl<'t, C: MusigContext> SignerAwaitingShares<C> {
     /// Assemble trusted signature shares (e.g. when all keys owned by one signer)
-    pub fn receive_trusted_shares(self, shares: Vec<Scalar>) -> Signature {
+    pub fn receive_trusted_shares(self, shares: &[Scalar]) -> Signature {
+        protocol_span!(
+            "musig.receive_trusted_shares",
+            self.position,
+            &self.transcript
+        );
+
         // s = sum(s_i), s_i = shares[i]
-        let s: Scalar = shares.into_iter().map(|share| share).sum();
+        let s: Scalar = shares.iter().sum();
         Signature {
             s,
             R: self.R.compress(),
         }
     }
 
+    /// Verify and assemble signature shares received as `(position, share)` pairs in any order,
+    /// e.g. in the order they arrived from the network.
+    /// Fails with `SigningError::MissingParticipants` unless there is exactly one share for each position,
+    /// counting the positions that received exactly one share as received.
+    pub fn receive_indexed_shares(
+        self,
+        shares: &[(usize, Scalar)],
+    ) -> Result<Signature, SigningError> {
+        let n = self.counterparties.len();
+        let mut ordered = vec![Vec::new(); n];
+        for &(position, share) in shares {
+            if let Some(slot) = ordered.get_mut(position) {
+                slot.push(share);
+            }
+        }
+        let received = ordered.iter().filter(|slot| slot.len() == 1).count();
+        if received != n || shares.len() != n {
+            return Err(SigningError::MissingParticipants {
+                expected: n,
+                received,
+            });
+        }
+        let ordered: Vec<_> = ordered.into_iter().map(|slot| slot[0]).collect();
+        self.receive_shares(&ordered)
+    }
+
     /// Verify and assemble signature shares.
-    pub fn receive_shares(self, shares: Vec<Scalar>) -> Result<Signature, MusigError> {
+    /// Fails with `SigningError::MissingParticipants` unless there is one share per party,
+    /// and with `SigningError::ShareMismatch` if a share fails to verify.
+    pub fn receive_shares(self, shares: &[Scalar]) -> Result<Signature, SigningError> {
+        protocol_span!("musig.receive_shares", self.position, &self.transcript);
+        SigningError::check_count(self.counterparties.len(), shares.len())?;
+
         // Move out self's fields because `self.c` inside `map`'s closure would
         // lead to capturing `self` by reference, while we want
         // to move `self.counterparties` out of it.
@@ -178,7 +249,7 @@ impl<'t, C: MusigContext> SignerAwaitingShares<C> {
         let s = self
             .counterparties
             .into_iter()
-            .zip(shares)
+            .zip(shares.iter().copied())
             .map(|(counterparty, share)| counterparty.verify_share(share, context, &transcript))
             .sum::<Result<_, _>>()?;
 
@@ -2,9 +2,16 @@ use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 
-use starsig::{Signature, TranscriptProtocol, VerificationKey};
+use starsig::{BatchVerifier, Signature, StarsigError, TranscriptProtocol, VerificationKey};
 
-use crate::{Multikey, Multimessage, Multisignature, MusigContext, MusigError, Signer};
+use crate::{
+    aggregate_keys, aggregate_keys_bytes, aggregate_many, sign_aggregate, split_secret,
+    verify_share, AggregationProof, BatchParty, BatchSigner, Multikey, MultikeyAccumulator,
+    MultikeyN, Multimessage, Multisignature, MusigContext, MusigError, NonceCommitment,
+    NoncePrecommitment, PrecommitmentTree, SharedKey, Signer, SignerCodec, SignerMessage, SignerN,
+    SignerState, SigningError, TxSigner, LABEL_AGGREGATION, LABEL_COEFFICIENT, LABEL_INDEX,
+    LABEL_KEY, LABEL_KEY_COUNT,
+};
 
 #[test]
 fn sign_verify_single_multikey() {
@@ -91,17 +98,17 @@ fn sign_with_mpc<C: MusigContext + Clone>(
 
     let (parties, comms): (Vec<_>, Vec<_>) = parties
         .into_iter()
-        .map(|p| p.receive_precommitments(precomms.clone()))
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
         .unzip();
 
     let (parties, shares): (Vec<_>, Vec<_>) = parties
         .into_iter()
-        .map(|p| p.receive_commitments(comms.clone()).unwrap())
+        .map(|p| p.receive_commitments(&comms).unwrap())
         .unzip();
 
     let signatures: Vec<Signature> = parties
         .into_iter()
-        .map(|p| p.receive_shares(shares.clone()).unwrap())
+        .map(|p| p.receive_shares(&shares).unwrap())
         .collect();
 
     // Check that signatures from all parties are the same
@@ -302,3 +309,2048 @@ fn check_transcripts_multimessage() {
     // Test that prover and verifier transcript states are the same after running protocol
     assert_eq!(prover_challenge, verifier_challenge);
 }
+
+#[test]
+fn nonce_commitment_types() {
+    let priv_keys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&priv_keys);
+    let mut transcripts: Vec<_> = priv_keys
+        .iter()
+        .map(|_| Transcript::new(b"example transcript"))
+        .collect();
+
+    let (parties, precomms): (Vec<_>, Vec<NoncePrecommitment>) = priv_keys
+        .iter()
+        .zip(transcripts.iter_mut())
+        .enumerate()
+        .map(|(i, (x_i, t))| Signer::new(t, i, *x_i, multikey.clone()))
+        .unzip();
+
+    // Precommitments and commitments travel over the network as bytes.
+    let precomms: Vec<_> = precomms
+        .iter()
+        .map(|p| NoncePrecommitment::from_bytes(p.to_bytes()))
+        .collect();
+
+    let (parties, comms): (Vec<_>, Vec<NonceCommitment>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+
+    let comms: Vec<_> = comms
+        .iter()
+        .map(|c| NonceCommitment::from_point(c.into_point()))
+        .collect();
+
+    // Swapping two commitments must not match the precommitments.
+    let mut swapped = comms.clone();
+    swapped.swap(0, 1);
+
+    let results: Vec<_> = parties
+        .into_iter()
+        .map(|p| p.receive_commitments(&swapped))
+        .collect();
+    for result in results {
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn multikey_binds_participant_count() {
+    let priv_keys = vec![
+        Scalar::from(1u64),
+        Scalar::from(2u64),
+        Scalar::from(3u64),
+        Scalar::from(4u64),
+    ];
+    let multikey = multikey_helper(&priv_keys);
+
+    let (signature, _) = sign_with_mpc(
+        &priv_keys,
+        multikey.clone(),
+        Transcript::new(b"example transcript"),
+    )
+    .unwrap();
+
+    assert!(signature
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            multikey.aggregated_key()
+        )
+        .is_ok());
+
+    // Claiming only 3 of the 4 parties yields a different aggregated key.
+    let multikey_3 = multikey_helper(&priv_keys[..3].to_vec());
+    assert!(signature
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            multikey_3.aggregated_key()
+        )
+        .is_err());
+
+    // Claiming a 5th party yields a different aggregated key as well.
+    let mut priv_keys_5 = priv_keys.clone();
+    priv_keys_5.push(Scalar::from(5u64));
+    let multikey_5 = multikey_helper(&priv_keys_5);
+    assert!(signature
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            multikey_5.aggregated_key()
+        )
+        .is_err());
+}
+
+#[test]
+fn sign_aggregate_verifies() {
+    let priv_keys = vec![
+        Scalar::from(1u64),
+        Scalar::from(2u64),
+        Scalar::from(3u64),
+        Scalar::from(4u64),
+    ];
+
+    let (multikey, signature) =
+        sign_aggregate(&mut Transcript::new(b"example transcript"), &priv_keys).unwrap();
+
+    assert_eq!(
+        multikey.aggregated_key(),
+        multikey_helper(&priv_keys).aggregated_key()
+    );
+    assert!(signature
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            multikey.aggregated_key()
+        )
+        .is_ok());
+    assert!(signature
+        .verify(
+            &mut Transcript::new(b"another transcript"),
+            multikey.aggregated_key()
+        )
+        .is_err());
+
+    // Single key is passed as-is.
+    let (multikey, signature) =
+        sign_aggregate(&mut Transcript::new(b"example transcript"), &priv_keys[..1]).unwrap();
+    assert!(signature
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            multikey.aggregated_key()
+        )
+        .is_ok());
+
+    assert_eq!(
+        sign_aggregate(&mut Transcript::new(b"example transcript"), &[]).map(|_| ()),
+        Err(MusigError::BadArguments)
+    );
+}
+
+#[test]
+fn error_source_chaining() {
+    use std::error::Error;
+
+    fn verify_as_musig(signature: &Signature, pubkey: VerificationKey) -> Result<(), MusigError> {
+        signature.verify(&mut Transcript::new(b"example transcript"), pubkey)?;
+        Ok(())
+    }
+
+    let privkey = Scalar::from(1u64);
+    let signature = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let wrong_pubkey = VerificationKey::from_secret(&Scalar::from(2u64));
+
+    let err = verify_as_musig(&signature, wrong_pubkey).unwrap_err();
+    assert_eq!(
+        err,
+        MusigError::SignatureError(StarsigError::InvalidSignature)
+    );
+
+    let source = err
+        .source()
+        .and_then(|e| e.downcast_ref::<StarsigError>())
+        .unwrap();
+    assert_eq!(source, &StarsigError::InvalidSignature);
+
+    // Variants without an underlying cause have no source.
+    assert!(MusigError::BadArguments.source().is_none());
+}
+
+#[test]
+fn precommitment_vector() {
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
+
+    let commitment = NonceCommitment::from_point(RISTRETTO_BASEPOINT_COMPRESSED);
+    let multikey = Multikey::new(vec![VerificationKey::from_compressed(
+        RISTRETTO_BASEPOINT_COMPRESSED,
+    )])
+    .unwrap();
+    let session = NonceCommitment::precommitment_transcript(&multikey);
+    let precommitment = commitment.precommit(&session);
+
+    let expected = [
+        43, 252, 232, 241, 12, 86, 69, 161, 166, 1, 174, 0, 40, 231, 28, 138, 130, 179, 10, 26, 85,
+        85, 31, 187, 113, 251, 68, 42, 24, 111, 19, 5,
+    ];
+    assert_eq!(precommitment.to_bytes(), expected);
+
+    // Same input hashed under a different label yields a different value.
+    let mut t = Transcript::new(b"Musig.another-protocol");
+    t.append_point(b"R", &RISTRETTO_BASEPOINT_COMPRESSED);
+    let mut other = [0u8; 32];
+    t.challenge_bytes(b"precommitment", &mut other);
+    assert_ne!(precommitment.to_bytes(), other);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Aggregated key does not match")]
+fn tampered_aggregation_factor() {
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use curve25519_dalek::traits::VartimeMultiscalarMul;
+
+    let points: Vec<_> = (1u64..4)
+        .map(|x| VerificationKey::from_secret_decompressed(&Scalar::from(x)))
+        .collect();
+    let mut factors = vec![Scalar::from(5u64), Scalar::from(6u64), Scalar::from(7u64)];
+    let aggregated_key = RistrettoPoint::vartime_multiscalar_mul(&factors, &points);
+
+    // Sanity check: the untampered factors pass.
+    Multikey::debug_assert_aggregation(&factors, &points, &aggregated_key);
+
+    factors[1] += Scalar::one();
+    Multikey::debug_assert_aggregation(&factors, &points, &aggregated_key);
+}
+
+#[test]
+fn multikey_versioned_roundtrip() {
+    let priv_keys = vec![
+        Scalar::from(1u64),
+        Scalar::from(2u64),
+        Scalar::from(3u64),
+        Scalar::from(4u64),
+    ];
+    let multikey = multikey_helper(&priv_keys);
+
+    let bytes = multikey.serialize_versioned();
+    assert_eq!(bytes.len(), 1 + 4 + 5 * 32);
+    assert_eq!(bytes[0], 1);
+
+    let decoded = Multikey::deserialize_versioned(&bytes).unwrap();
+    assert_eq!(decoded.aggregated_key(), multikey.aggregated_key());
+    assert_eq!(decoded.len(), multikey.len());
+    for i in 0..multikey.len() {
+        assert_eq!(decoded.key(i), multikey.key(i));
+    }
+
+    // Single key multikey
+    let single = multikey_helper(&priv_keys[..1].to_vec());
+    let decoded = Multikey::deserialize_versioned(&single.serialize_versioned()).unwrap();
+    assert_eq!(decoded.aggregated_key(), single.aggregated_key());
+}
+
+#[test]
+fn multikey_versioned_errors() {
+    let priv_keys = vec![Scalar::from(1u64), Scalar::from(2u64)];
+    let bytes = multikey_helper(&priv_keys).serialize_versioned();
+
+    let mut unknown_version = bytes.clone();
+    unknown_version[0] = 3;
+    assert_eq!(
+        Multikey::deserialize_versioned(&unknown_version).map(|_| ()),
+        Err(MusigError::UnsupportedVersion(3))
+    );
+
+    assert_eq!(
+        Multikey::deserialize_versioned(&bytes[..bytes.len() - 1]).map(|_| ()),
+        Err(MusigError::InvalidFormat)
+    );
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        Multikey::deserialize_versioned(&trailing).map(|_| ()),
+        Err(MusigError::InvalidFormat)
+    );
+
+    // Invalid point in place of the second key
+    let mut bad_key = bytes.clone();
+    for b in &mut bad_key[1 + 4 + 32..1 + 4 + 64] {
+        *b = 0xff;
+    }
+    assert_eq!(
+        Multikey::deserialize_versioned(&bad_key).map(|_| ()),
+        Err(MusigError::InvalidPoint)
+    );
+
+    // Aggregated key that does not match the keys
+    let mut bad_aggregate = bytes.clone();
+    let n = bad_aggregate.len();
+    bad_aggregate[n - 32..].copy_from_slice(&bytes[1 + 4..1 + 4 + 32]);
+    assert_eq!(
+        Multikey::deserialize_versioned(&bad_aggregate).map(|_| ()),
+        Err(MusigError::InvalidFormat)
+    );
+}
+
+#[test]
+fn coordinator_verifies_shares() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let transcript = Transcript::new(b"example transcript");
+
+    let mut transcripts: Vec<_> = privkeys.iter().map(|_| transcript.clone()).collect();
+    let (parties, precomms): (Vec<_>, Vec<_>) = privkeys
+        .iter()
+        .zip(transcripts.iter_mut())
+        .enumerate()
+        .map(|(i, (x_i, t))| Signer::new(t, i, *x_i, multikey.clone()))
+        .unzip();
+    let (parties, comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+    let shares: Vec<_> = parties
+        .into_iter()
+        .map(|p| p.receive_commitments(&comms).unwrap().1)
+        .collect();
+
+    // The coordinator holds no secrets: only the public context, nonce commitments and shares.
+    let mut coordinator = transcript.clone();
+    multikey.commit(&mut coordinator);
+    coordinator.append_point(b"R", &NonceCommitment::sum(&comms).unwrap().compress());
+    let challenges: Vec<_> = (0..privkeys.len())
+        .map(|i| multikey.challenge(i, &mut coordinator.clone()))
+        .collect();
+
+    for i in 0..privkeys.len() {
+        assert!(verify_share(shares[i], comms[i], challenges[i], multikey.key(i)).is_ok());
+    }
+
+    let corrupted = shares[1] + Scalar::one();
+    assert_eq!(
+        verify_share(corrupted, comms[1], challenges[1], multikey.key(1)),
+        Err(MusigError::ShareError {
+            pubkey: multikey.key(1).to_bytes()
+        })
+    );
+}
+
+#[test]
+fn slice_api_full_session() {
+    // Messages are kept in fixed arrays and borrowed by every party,
+    // so no per-party copies of the collected messages are made.
+    let privkeys = [Scalar::from(1u64), Scalar::from(2u64)];
+    let multikey = multikey_helper(&privkeys.to_vec());
+    let mut t0 = Transcript::new(b"example transcript");
+    let mut t1 = Transcript::new(b"example transcript");
+
+    let (p0, precomm0) = Signer::new(&mut t0, 0, privkeys[0], multikey.clone());
+    let (p1, precomm1) = Signer::new(&mut t1, 1, privkeys[1], multikey.clone());
+    let precomms = [precomm0, precomm1];
+
+    let (p0, comm0) = p0.receive_precommitments(&precomms).unwrap();
+    let (p1, comm1) = p1.receive_precommitments(&precomms).unwrap();
+    let comms = [comm0, comm1];
+
+    let (p0, share0) = p0.receive_commitments(&comms).unwrap();
+    let (p1, share1) = p1.receive_commitments(&comms).unwrap();
+    let shares = [share0, share1];
+
+    let sig0 = p0.receive_shares(&shares).unwrap();
+    let sig1 = p1.receive_trusted_shares(&shares);
+    assert_eq!(sig0.to_bytes(), sig1.to_bytes());
+    assert!(sig0
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            multikey.aggregated_key()
+        )
+        .is_ok());
+}
+
+#[cfg(feature = "tracing")]
+#[tracing_test::traced_test]
+#[test]
+fn tracing_spans_full_session() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64)];
+    let multikey = multikey_helper(&privkeys);
+    let (signature, _) = sign_with_mpc(
+        &privkeys,
+        multikey.clone(),
+        Transcript::new(b"example transcript"),
+    )
+    .unwrap();
+    assert!(signature
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            multikey.aggregated_key()
+        )
+        .is_ok());
+
+    let messages = vec![b"message1", b"message2"];
+    let multimessage = Multimessage::new(multimessage_helper(&privkeys, messages.clone()));
+    let (signature, _) = sign_with_mpc(
+        &privkeys,
+        multimessage,
+        Transcript::new(b"example transcript"),
+    )
+    .unwrap();
+    assert!(signature
+        .verify_multi(
+            &mut Transcript::new(b"example transcript"),
+            multimessage_helper(&privkeys, messages)
+        )
+        .is_ok());
+
+    for span in &[
+        "musig.new",
+        "musig.receive_precommitments",
+        "musig.receive_commitments",
+        "musig.receive_shares",
+        "musig.verify_share",
+        "starsig.verify",
+        "musig.verify_multi",
+    ] {
+        assert!(logs_contain(span));
+    }
+    assert!(logs_contain("position=1"));
+    assert!(logs_contain("transcript="));
+}
+
+#[test]
+fn multikey_rejects_duplicate_keys() {
+    let X = VerificationKey::from_secret(&Scalar::from(1u64));
+    let Y = VerificationKey::from_secret(&Scalar::from(2u64));
+    let duplicate = Some(MusigError::DuplicateKey {
+        pubkey: X.to_bytes(),
+    });
+
+    // All parties submitting the same key is rejected rather than aggregated.
+    assert_eq!(Multikey::new(vec![X; 4]).err(), duplicate);
+    assert_eq!(Multikey::new(vec![X, Y, X]).err(), duplicate);
+    assert!(Multikey::new(vec![X, Y]).is_ok());
+}
+
+#[test]
+fn fixed_size_session() {
+    let x = [Scalar::from(1u64), Scalar::from(2u64)];
+    let keys = [
+        VerificationKey::from_secret(&x[0]),
+        VerificationKey::from_secret(&x[1]),
+    ];
+    let multikey = MultikeyN::new(keys).unwrap();
+    assert_eq!(
+        multikey.aggregated_key(),
+        Multikey::new(keys.to_vec()).unwrap().aggregated_key()
+    );
+
+    let mut t0 = Transcript::new(b"example transcript");
+    let mut t1 = Transcript::new(b"example transcript");
+    let (p0, precomm0) = SignerN::new(&mut t0, 0, x[0], multikey.clone());
+    let (p1, precomm1) = SignerN::new(&mut t1, 1, x[1], multikey.clone());
+    let precomms = [precomm0, precomm1];
+
+    let (p0, comm0) = p0.receive_precommitments(&precomms);
+    let (p1, comm1) = p1.receive_precommitments(&precomms);
+    let comms = [comm0, comm1];
+
+    let (p0, share0) = p0.receive_commitments(&comms).unwrap();
+    let (p1, share1) = p1.receive_commitments(&comms).unwrap();
+    let shares = [share0, share1];
+
+    let sig = p0.receive_shares(&shares).unwrap();
+    assert_eq!(
+        sig.to_bytes(),
+        p1.receive_shares(&shares).unwrap().to_bytes()
+    );
+    assert!(sig
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            multikey.aggregated_key()
+        )
+        .is_ok());
+}
+
+#[test]
+fn tx_signer_flow() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+
+    // Mock transaction transcript, as in ZkVM `signtx`.
+    let mut tx_transcript = Transcript::new(b"ZkVM.signtx");
+    tx_transcript.append_message(b"txid", &[42u8; 32]);
+
+    let mut tx_signers: Vec<_> = privkeys
+        .iter()
+        .map(|_| TxSigner::new(tx_transcript.clone(), multikey.clone()))
+        .collect();
+
+    // An abandoned session does not affect the next one.
+    for (i, (tx_signer, x_i)) in tx_signers.iter_mut().zip(privkeys.iter()).enumerate() {
+        let _ = tx_signer.start(i, *x_i);
+    }
+
+    let (parties, precomms): (Vec<_>, Vec<_>) = tx_signers
+        .iter_mut()
+        .zip(privkeys.iter())
+        .enumerate()
+        .map(|(i, (tx_signer, x_i))| tx_signer.start(i, *x_i))
+        .unzip();
+    let (parties, comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+    let (parties, shares): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_commitments(&comms).unwrap())
+        .unzip();
+    let signatures: Vec<_> = parties
+        .into_iter()
+        .map(|p| p.receive_shares(&shares).unwrap())
+        .collect();
+
+    for (tx_signer, sig) in tx_signers.iter().zip(signatures.iter()) {
+        assert!(tx_signer.verify(sig).is_ok());
+    }
+    assert!(signatures[0]
+        .verify(&mut tx_transcript.clone(), multikey.aggregated_key())
+        .is_ok());
+
+    // Signature does not verify for another transaction.
+    let mut other_tx = Transcript::new(b"ZkVM.signtx");
+    other_tx.append_message(b"txid", &[43u8; 32]);
+    assert!(TxSigner::new(other_tx, multikey)
+        .verify(&signatures[0])
+        .is_err());
+}
+
+#[test]
+fn weighted_multikey() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let keys: Vec<_> = privkeys.iter().map(VerificationKey::from_secret).collect();
+    let weighted = |weights: &[u64]| {
+        let pairs: Vec<_> = keys.iter().cloned().zip(weights.iter().cloned()).collect();
+        Multikey::new_weighted(&pairs).unwrap()
+    };
+    let multikey = weighted(&[1, 5, 10]);
+    assert_eq!(multikey.weights(), Some(&[1, 5, 10][..]));
+
+    let (sig, _) = sign_with_mpc(
+        &privkeys,
+        multikey.clone(),
+        Transcript::new(b"example transcript"),
+    )
+    .unwrap();
+    assert!(sig
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            multikey.aggregated_key()
+        )
+        .is_ok());
+    for other in &[weighted(&[10, 5, 1]), multikey_helper(&privkeys)] {
+        assert!(sig
+            .verify(
+                &mut Transcript::new(b"example transcript"),
+                other.aggregated_key()
+            )
+            .is_err());
+    }
+
+    // Party 1 computes its share with mismatched weights
+    let contexts = [multikey.clone(), weighted(&[1, 1, 10]), multikey.clone()];
+    let mut transcripts: Vec<_> = privkeys
+        .iter()
+        .map(|_| Transcript::new(b"example transcript"))
+        .collect();
+    let (parties, precomms): (Vec<_>, Vec<_>) = privkeys
+        .iter()
+        .zip(transcripts.iter_mut())
+        .enumerate()
+        .map(|(i, (x_i, t))| Signer::new(t, i, *x_i, contexts[i].clone()))
+        .unzip();
+    let (parties, comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+    let (parties, shares): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_commitments(&comms).unwrap())
+        .unzip();
+    let mut parties = parties.into_iter();
+    assert_eq!(
+        parties.next().unwrap().receive_shares(&shares).err(),
+        Some(SigningError::ShareMismatch {
+            pubkey: keys[1].to_bytes()
+        })
+    );
+
+    // Zero weights are rejected
+    assert_eq!(
+        Multikey::new_weighted(&[(keys[0], 1), (keys[1], 0)]).err(),
+        Some(MusigError::BadArguments)
+    );
+
+    // Weights survive the versioned encoding
+    let bytes = multikey.serialize_versioned();
+    assert_eq!(bytes[0], 2);
+    let decoded = Multikey::deserialize_versioned(&bytes).unwrap();
+    assert_eq!(decoded.aggregated_key(), multikey.aggregated_key());
+    assert_eq!(decoded.weights(), multikey.weights());
+}
+
+#[test]
+fn multikey_accumulator() {
+    let keys: Vec<_> = (1..=5u64)
+        .map(|i| VerificationKey::from_secret(&Scalar::from(i)))
+        .collect();
+
+    for n in 1..=keys.len() {
+        let mut acc = MultikeyAccumulator::new();
+        for key in &keys[..n] {
+            acc.add_key(*key).unwrap();
+        }
+        assert_eq!(acc.len(), n);
+        let preview = acc.preview_aggregate().unwrap();
+        let multikey = acc.finalize().unwrap();
+        let expected = Multikey::new(keys[..n].to_vec()).unwrap();
+        assert_eq!(multikey.aggregated_key(), expected.aggregated_key());
+        assert_eq!(preview, expected.aggregated_key());
+        assert_eq!(preview, aggregate_keys(&keys[..n]).unwrap());
+    }
+
+    let mut acc = MultikeyAccumulator::new();
+    acc.add_key(keys[0]).unwrap();
+    assert_eq!(
+        acc.add_key(keys[0]),
+        Err(MusigError::DuplicateKey {
+            pubkey: keys[0].to_bytes()
+        })
+    );
+    assert_eq!(
+        MultikeyAccumulator::new().finalize().err(),
+        Some(MusigError::BadArguments)
+    );
+    assert_eq!(
+        MultikeyAccumulator::new().preview_aggregate(),
+        Err(MusigError::BadArguments)
+    );
+
+    // Malformed keys fail with the same errors as in `Multikey::new`.
+    let bad_key = VerificationKey::from_compressed(CompressedRistretto([0xff; 32]));
+    let mut acc = MultikeyAccumulator::new();
+    assert_eq!(
+        acc.add_key(bad_key),
+        Err(MusigError::InvalidKey { index: 0 })
+    );
+    assert!(acc.is_empty());
+    acc.add_key(keys[0]).unwrap();
+    assert_eq!(
+        acc.add_key(bad_key),
+        Err(MusigError::InvalidKey { index: 1 })
+    );
+    assert_eq!(acc.preview_aggregate(), Ok(keys[0]));
+}
+
+#[test]
+fn aggregate_keys_matches_multikey() {
+    let keys: Vec<_> = (1..=4u64)
+        .map(|i| VerificationKey::from_secret(&Scalar::from(i)))
+        .collect();
+    for n in 1..=keys.len() {
+        assert_eq!(
+            aggregate_keys(&keys[..n]).unwrap(),
+            Multikey::new(keys[..n].to_vec()).unwrap().aggregated_key()
+        );
+    }
+    assert_eq!(aggregate_keys(&[]), Err(MusigError::BadArguments));
+    assert_eq!(
+        aggregate_keys(&[keys[0], keys[0]]),
+        Err(MusigError::DuplicateKey {
+            pubkey: keys[0].to_bytes()
+        })
+    );
+
+    // Malformed keys fail with the same errors as in `Multikey::new`, including a single key.
+    let bad_key = VerificationKey::from_compressed(CompressedRistretto([0xff; 32]));
+    for bad_keys in &[vec![bad_key], vec![keys[0], bad_key, keys[1]]] {
+        assert_eq!(
+            aggregate_keys(bad_keys).err(),
+            Multikey::new(bad_keys.clone()).err()
+        );
+    }
+    assert_eq!(
+        aggregate_keys(&[bad_key]),
+        Err(MusigError::InvalidKey { index: 0 })
+    );
+}
+
+#[test]
+fn aggregate_many_matches_multikeys() {
+    let keys: Vec<_> = (1..=6u64)
+        .map(|i| VerificationKey::from_secret(&Scalar::from(i)))
+        .collect();
+    let groups: Vec<&[VerificationKey]> = vec![&keys[..1], &keys[..3], &keys[2..6], &keys[1..3]];
+    let aggregates = aggregate_many(&groups).unwrap();
+    assert_eq!(aggregates.len(), groups.len());
+    for (group, aggregate) in groups.iter().zip(aggregates) {
+        assert_eq!(
+            aggregate,
+            Multikey::new(group.to_vec()).unwrap().aggregated_key()
+        );
+    }
+
+    assert_eq!(aggregate_many(&[]), Ok(vec![]));
+    assert_eq!(
+        aggregate_many(&[&keys[..2], &[]]),
+        Err(MusigError::BadArguments)
+    );
+    assert_eq!(
+        aggregate_many(&[&[keys[0], keys[0]]]),
+        Err(MusigError::DuplicateKey {
+            pubkey: keys[0].to_bytes()
+        })
+    );
+
+    // Malformed keys fail with the same errors as in `Multikey::new`, including a single key.
+    let bad_key = VerificationKey::from_compressed(CompressedRistretto([0xff; 32]));
+    for bad_keys in &[vec![bad_key], vec![keys[0], bad_key, keys[1]]] {
+        assert_eq!(
+            aggregate_many(&[&keys[..2], bad_keys]).err(),
+            Multikey::new(bad_keys.clone()).err()
+        );
+    }
+    assert_eq!(
+        aggregate_many(&[&[bad_key]]),
+        Err(MusigError::InvalidKey { index: 0 })
+    );
+}
+
+#[test]
+fn signer_codec_roundtrip() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64)];
+    let multikey = multikey_helper(&privkeys);
+    let mut transcripts: Vec<_> = privkeys
+        .iter()
+        .map(|_| Transcript::new(b"example transcript"))
+        .collect();
+
+    // Every message goes through the codec over a single byte stream.
+    let relay = |messages: Vec<SignerMessage>| -> Vec<SignerMessage> {
+        let stream: Vec<u8> = messages
+            .iter()
+            .flat_map(SignerCodec::encode_message)
+            .collect();
+        let mut stream = &stream[..];
+        let decoded: Vec<_> = messages
+            .iter()
+            .map(|_| SignerCodec::decode_message(&mut stream).unwrap())
+            .collect();
+        assert!(stream.is_empty());
+        assert_eq!(decoded, messages);
+        decoded
+    };
+
+    let (parties, precomms): (Vec<_>, Vec<_>) = privkeys
+        .iter()
+        .zip(transcripts.iter_mut())
+        .enumerate()
+        .map(|(i, (x_i, t))| Signer::new(t, i, *x_i, multikey.clone()))
+        .unzip();
+    let precomms = relay(
+        precomms
+            .into_iter()
+            .map(SignerMessage::Precommitment)
+            .collect(),
+    )
+    .into_iter()
+    .map(|m| m.into_precommitment())
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+
+    let (parties, comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+    let comms = relay(comms.into_iter().map(SignerMessage::Commitment).collect())
+        .into_iter()
+        .map(|m| m.into_commitment())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    let (parties, shares): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_commitments(&comms).unwrap())
+        .unzip();
+    let shares = relay(shares.into_iter().map(SignerMessage::Share).collect())
+        .into_iter()
+        .map(|m| m.into_share())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    for p in parties {
+        let sig = p.receive_shares(&shares).unwrap();
+        assert!(sig
+            .verify(
+                &mut Transcript::new(b"example transcript"),
+                multikey.aggregated_key()
+            )
+            .is_ok());
+    }
+
+    // Malformed frames
+    let frame = SignerCodec::encode_message(&SignerMessage::Share(Scalar::one()));
+    assert_eq!(
+        SignerCodec::decode_message(&mut &frame[..frame.len() - 1]),
+        Err(MusigError::InvalidFormat)
+    );
+    let mut unknown = frame.clone();
+    unknown[0] = 9;
+    assert_eq!(
+        SignerCodec::decode_message(&mut &unknown[..]),
+        Err(MusigError::InvalidFormat)
+    );
+    let mut bad_len = frame.clone();
+    bad_len[1] = 31;
+    assert_eq!(
+        SignerCodec::decode_message(&mut &bad_len[..]),
+        Err(MusigError::InvalidFormat)
+    );
+
+    // A failed decode leaves the stream at the start of the frame.
+    let mut bad_share = frame.clone();
+    bad_share[5..].copy_from_slice(&[0xff; 32]);
+    let mut stream = &bad_share[..];
+    assert_eq!(
+        SignerCodec::decode_message(&mut stream),
+        Err(MusigError::InvalidFormat)
+    );
+    assert_eq!(stream, &bad_share[..]);
+    assert_eq!(
+        SignerMessage::Share(Scalar::one()).into_commitment(),
+        Err(MusigError::InvalidFormat)
+    );
+}
+
+#[test]
+fn claimed_aggregate_must_match() {
+    let keys: Vec<_> = (1..=3u64)
+        .map(|i| VerificationKey::from_secret(&Scalar::from(i)))
+        .collect();
+    let claimed = Multikey::new(keys.clone()).unwrap().aggregated_key();
+    assert!(Multikey::verify_aggregate_matches(&keys, claimed).is_ok());
+
+    // Coordinator lies about the aggregate, e.g. by summing the keys without the factors.
+    let tampered = VerificationKey::from(
+        keys.iter()
+            .map(|k| k.into_point().decompress().unwrap())
+            .sum::<curve25519_dalek::ristretto::RistrettoPoint>(),
+    );
+    assert_eq!(
+        Multikey::verify_aggregate_matches(&keys, tampered),
+        Err(MusigError::AggregatedKeyMismatch)
+    );
+    assert_eq!(
+        Multikey::verify_aggregate_matches(&keys[..2], claimed),
+        Err(MusigError::AggregatedKeyMismatch)
+    );
+}
+
+#[test]
+fn batch_signer_session() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let messages: Vec<&[u8]> = vec![b"one", b"two", b"three"];
+    let transcripts = || -> Vec<Transcript> {
+        messages
+            .iter()
+            .map(|m| {
+                let mut t = Transcript::new(b"example transcript");
+                t.append_message(b"message", m);
+                t
+            })
+            .collect()
+    };
+
+    let mut party_transcripts: Vec<_> = privkeys.iter().map(|_| transcripts()).collect();
+    let (parties, precomms): (Vec<_>, Vec<_>) = party_transcripts
+        .iter_mut()
+        .zip(privkeys.iter())
+        .enumerate()
+        .map(|(i, (t, x_i))| BatchSigner::new(t, i, *x_i, multikey.clone()))
+        .unzip();
+    let (parties, comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+    let (parties, shares): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_commitments(&comms).unwrap())
+        .unzip();
+    let signatures: Vec<Vec<Signature>> = parties
+        .into_iter()
+        .map(|p| p.receive_shares(&shares).unwrap())
+        .collect();
+
+    let signatures = &signatures[0];
+    assert_eq!(signatures.len(), 3);
+    for (sig, mut t) in signatures.iter().zip(transcripts()) {
+        assert!(sig.verify(&mut t, multikey.aggregated_key()).is_ok());
+    }
+    // Independent nonces per message
+    assert_ne!(signatures[0].R, signatures[1].R);
+    // Signatures do not verify for other messages
+    assert!(signatures[0]
+        .verify(&mut transcripts()[1], multikey.aggregated_key())
+        .is_err());
+}
+
+#[test]
+fn borrowed_multikey_session() {
+    // Every party borrows the same multikey instead of cloning it.
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let mut transcripts: Vec<_> = privkeys
+        .iter()
+        .map(|_| Transcript::new(b"example transcript"))
+        .collect();
+
+    let (parties, precomms): (Vec<_>, Vec<_>) = transcripts
+        .iter_mut()
+        .zip(privkeys.iter())
+        .enumerate()
+        .map(|(i, (t, x_i))| Signer::new(t, i, *x_i, &multikey))
+        .unzip();
+    let (parties, comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+    let (parties, shares): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_commitments(&comms).unwrap())
+        .unzip();
+    let signatures: Vec<Signature> = parties
+        .into_iter()
+        .map(|p| p.receive_shares(&shares).unwrap())
+        .collect();
+
+    for sig in &signatures {
+        assert!(sig
+            .verify(
+                &mut Transcript::new(b"example transcript"),
+                multikey.aggregated_key()
+            )
+            .is_ok());
+    }
+}
+
+#[test]
+fn aggregation_uses_public_labels() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+
+    // Reconstruct the aggregated key with raw Merlin calls and the exported labels.
+    let mut prf = Transcript::new(LABEL_AGGREGATION);
+    prf.append_u64(LABEL_KEY_COUNT, privkeys.len() as u64);
+    for x in &privkeys {
+        prf.append_message(LABEL_KEY, VerificationKey::from_secret(x).as_bytes());
+    }
+    let aggregated_privkey: Scalar = privkeys
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let mut t = prf.clone();
+            t.append_u64(LABEL_INDEX, i as u64);
+            let mut buf = [0u8; 64];
+            t.challenge_bytes(LABEL_COEFFICIENT, &mut buf);
+            Scalar::from_bytes_mod_order_wide(&buf) * x
+        })
+        .sum();
+
+    assert_eq!(
+        VerificationKey::from_secret(&aggregated_privkey),
+        multikey.aggregated_key()
+    );
+}
+
+#[test]
+fn batch_signer_rejects_nonce_reuse() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64)];
+    let multikey = multikey_helper(&privkeys);
+    let transcripts = || -> Vec<Transcript> {
+        [b"one", b"two"]
+            .iter()
+            .map(|m| {
+                let mut t = Transcript::new(b"example transcript");
+                t.append_message(b"message", *m);
+                t
+            })
+            .collect()
+    };
+
+    let mut party_transcripts: Vec<_> = privkeys.iter().map(|_| transcripts()).collect();
+    let (parties, precomms): (Vec<_>, Vec<_>) = party_transcripts
+        .iter_mut()
+        .zip(privkeys.iter())
+        .enumerate()
+        .map(|(i, (t, x_i))| BatchSigner::new(t, i, *x_i, &multikey))
+        .unzip();
+    let (mut parties, mut comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+
+    // Coordinator replays the second party's nonce commitment for the second message.
+    comms[1][1] = comms[1][0];
+    let party = parties.remove(0);
+    assert_eq!(
+        party.receive_commitments(&comms).err(),
+        Some(SigningError::NonceReuse)
+    );
+}
+
+#[test]
+fn batch_heterogeneous_multikeys() {
+    let label: &'static [u8] = b"payment";
+    let transcript_for = |message: &[u8]| {
+        let mut t = Transcript::new(b"Starsig.sign_message");
+        t.append_message(label, message);
+        t
+    };
+
+    let privkeys1 = vec![Scalar::from(1u64), Scalar::from(2u64)];
+    let privkeys2 = vec![Scalar::from(3u64), Scalar::from(4u64), Scalar::from(5u64)];
+    let multikey1 = multikey_helper(&privkeys1);
+    let multikey2 = multikey_helper(&privkeys2);
+    let (sig1, _) = sign_with_mpc(&privkeys1, multikey1.clone(), transcript_for(b"one")).unwrap();
+    let (sig2, _) = sign_with_mpc(&privkeys2, multikey2.clone(), transcript_for(b"two")).unwrap();
+
+    // Plain batch over transcripts, with a different aggregated key for each item.
+    let mut batch = BatchVerifier::new(rand::thread_rng());
+    sig1.verify_batched(
+        &mut transcript_for(b"one"),
+        multikey1.aggregated_key(),
+        &mut batch,
+    );
+    sig2.verify_batched(
+        &mut transcript_for(b"two"),
+        multikey2.aggregated_key(),
+        &mut batch,
+    );
+    assert!(batch.verify().is_ok());
+
+    assert!(Multikey::verify_batch(
+        label,
+        &[(&multikey1, b"one", &sig1), (&multikey2, b"two", &sig2)]
+    )
+    .is_ok());
+    assert!(Multikey::verify_batch(
+        label,
+        &[(&multikey2, b"one", &sig1), (&multikey1, b"two", &sig2)]
+    )
+    .is_err());
+}
+
+#[test]
+fn precommitment_tree_inclusion() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let mut transcripts: Vec<_> = privkeys
+        .iter()
+        .map(|_| Transcript::new(b"example transcript"))
+        .collect();
+    let (_parties, precomms): (Vec<_>, Vec<_>) = transcripts
+        .iter_mut()
+        .zip(privkeys.iter())
+        .enumerate()
+        .map(|(i, (t, x_i))| Signer::new(t, i, *x_i, &multikey))
+        .unzip();
+
+    // Coordinator commits to all the precommitments and sends each party its path.
+    let tree = PrecommitmentTree::new(&precomms);
+    for (i, precomm) in precomms.iter().enumerate() {
+        let path = tree.path(i).unwrap();
+        assert!(PrecommitmentTree::verify_inclusion(&tree.root(), i, *precomm, &path).is_ok());
+    }
+    assert!(tree.path(3).is_none());
+
+    // A party whose precommitment was replaced by the coordinator detects it.
+    let mut tampered = precomms.clone();
+    tampered[1] = NoncePrecommitment::from_bytes([0u8; 32]);
+    let tree = PrecommitmentTree::new(&tampered);
+    let path = tree.path(1).unwrap();
+    assert_eq!(
+        PrecommitmentTree::verify_inclusion(&tree.root(), 1, precomms[1], &path),
+        Err(MusigError::PrecommitmentNotIncluded)
+    );
+    // Precommitment is bound to its position.
+    let path = tree.path(0).unwrap();
+    assert_eq!(
+        PrecommitmentTree::verify_inclusion(&tree.root(), 2, precomms[0], &path),
+        Err(MusigError::PrecommitmentNotIncluded)
+    );
+}
+
+#[test]
+fn multikey_reports_invalid_key_index() {
+    let bad_key = VerificationKey::from_compressed(CompressedRistretto([0xff; 32]));
+    let keys = vec![
+        VerificationKey::from_secret(&Scalar::from(1u64)),
+        bad_key,
+        VerificationKey::from_secret(&Scalar::from(2u64)),
+    ];
+    assert_eq!(
+        Multikey::new(keys).err(),
+        Some(MusigError::InvalidKey { index: 1 })
+    );
+    assert_eq!(
+        Multikey::new(vec![bad_key]).err(),
+        Some(MusigError::InvalidKey { index: 0 })
+    );
+}
+
+#[test]
+fn batch_party_signs_many_messages() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let messages: Vec<&[u8]> = vec![b"one", b"two", b"three"];
+    let transcripts = || -> Vec<Transcript> {
+        messages
+            .iter()
+            .map(|m| {
+                let mut t = Transcript::new(b"example transcript");
+                t.append_message(b"message", m);
+                t
+            })
+            .collect()
+    };
+
+    let batch_parties: Vec<_> = privkeys
+        .iter()
+        .enumerate()
+        .map(|(i, x_i)| BatchParty::new(&multikey, i, *x_i))
+        .collect();
+    let mut party_transcripts: Vec<_> = privkeys.iter().map(|_| transcripts()).collect();
+    let (parties, precomms): (Vec<_>, Vec<_>) = batch_parties
+        .iter()
+        .zip(party_transcripts.iter_mut())
+        .map(|(p, t)| p.start(t))
+        .unzip();
+    let (parties, comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+    let (parties, shares): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_commitments(&comms).unwrap())
+        .unzip();
+    let signatures: Vec<Vec<Signature>> = parties
+        .into_iter()
+        .map(|p| p.receive_shares(&shares).unwrap())
+        .collect();
+
+    assert_eq!(signatures[0].len(), 3);
+    for (sig, mut t) in signatures[0].iter().zip(transcripts()) {
+        assert!(sig.verify(&mut t, multikey.aggregated_key()).is_ok());
+    }
+}
+
+#[test]
+fn indexed_shares_in_any_order() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let mut transcripts: Vec<_> = privkeys
+        .iter()
+        .map(|_| Transcript::new(b"example transcript"))
+        .collect();
+    let (parties, precomms): (Vec<_>, Vec<_>) = transcripts
+        .iter_mut()
+        .zip(privkeys.iter())
+        .enumerate()
+        .map(|(i, (t, x_i))| Signer::new(t, i, *x_i, &multikey))
+        .unzip();
+    let (parties, comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+    let (mut parties, shares): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_commitments(&comms).unwrap())
+        .unzip();
+
+    let shuffled = [(2, shares[2]), (0, shares[0]), (1, shares[1])];
+    let sig = parties.remove(0).receive_indexed_shares(&shuffled).unwrap();
+    assert!(sig
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            multikey.aggregated_key()
+        )
+        .is_ok());
+
+    let missing = [(2, shares[2]), (0, shares[0])];
+    assert_eq!(
+        parties.remove(0).receive_indexed_shares(&missing).err(),
+        Some(SigningError::MissingParticipants {
+            expected: 3,
+            received: 2
+        })
+    );
+    let repeated = [(2, shares[2]), (0, shares[0]), (0, shares[0])];
+    assert_eq!(
+        parties.remove(0).receive_indexed_shares(&repeated).err(),
+        Some(SigningError::MissingParticipants {
+            expected: 3,
+            received: 1
+        })
+    );
+}
+
+#[test]
+fn aggregate_keys_from_bytes() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let mut bytes: Vec<[u8; 32]> = privkeys
+        .iter()
+        .map(|x| VerificationKey::from_secret(x).to_bytes())
+        .collect();
+    assert_eq!(
+        aggregate_keys_bytes(&bytes),
+        Ok(multikey.aggregated_key().to_bytes())
+    );
+
+    bytes[2] = [0xff; 32];
+    assert_eq!(
+        aggregate_keys_bytes(&bytes),
+        Err(MusigError::InvalidKey { index: 2 })
+    );
+    assert_eq!(
+        aggregate_keys_bytes(&bytes[2..]),
+        Err(MusigError::InvalidKey { index: 0 })
+    );
+}
+
+#[test]
+fn additive_shares_sign_for_secret() {
+    let secret = Scalar::from(42u64);
+    let shares = split_secret(secret, 3, &mut rand::thread_rng()).unwrap();
+    assert_eq!(shares.len(), 3);
+    assert_eq!(shares.iter().sum::<Scalar>(), secret);
+    assert!(split_secret(secret, 0, &mut rand::thread_rng()).is_err());
+
+    let shared_key =
+        SharedKey::new(shares.iter().map(VerificationKey::from_secret).collect()).unwrap();
+    assert_eq!(
+        shared_key.aggregated_key(),
+        VerificationKey::from_secret(&secret)
+    );
+
+    let (sig, _) = sign_with_mpc(
+        &shares,
+        shared_key.clone(),
+        Transcript::new(b"example transcript"),
+    )
+    .unwrap();
+    assert!(sig
+        .verify(
+            &mut Transcript::new(b"example transcript"),
+            VerificationKey::from_secret(&secret)
+        )
+        .is_ok());
+}
+
+#[test]
+fn mismatched_key_list_session() {
+    // Party 0 was given a different list of keys than the other parties.
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let other_multikey = multikey_helper(&vec![privkeys[0], privkeys[1], Scalar::from(4u64)]);
+    let mut transcripts: Vec<_> = privkeys
+        .iter()
+        .map(|_| Transcript::new(b"example transcript"))
+        .collect();
+
+    let (parties, precomms): (Vec<_>, Vec<_>) = transcripts
+        .iter_mut()
+        .zip(privkeys.iter())
+        .enumerate()
+        .map(|(i, (t, x_i))| {
+            let context = if i == 0 { &other_multikey } else { &multikey };
+            Signer::new(t, i, *x_i, context)
+        })
+        .unzip();
+    let (parties, comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+
+    // Nobody can open the precommitments made over the other key list.
+    for party in parties {
+        match party.receive_commitments(&comms) {
+            Err(SigningError::Equivocation { .. }) => {}
+            _ => panic!("expected an equivocation"),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn async_coordinator_session() {
+    use crate::AsyncCoordinator;
+    use std::sync::Arc;
+
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let coordinator = Arc::new(AsyncCoordinator::new(privkeys.len()));
+
+    // Each party runs in its own task and awaits the coordinator between the rounds.
+    let handles: Vec<_> = privkeys
+        .iter()
+        .copied()
+        .enumerate()
+        .map(|(i, x_i)| {
+            let coordinator = coordinator.clone();
+            let multikey = multikey.clone();
+            tokio::spawn(async move {
+                let mut transcript = Transcript::new(b"example transcript");
+                let (party, precomm) = Signer::new(&mut transcript, i, x_i, multikey);
+                let precomms = coordinator.submit_precommitment(i, precomm).await?;
+                let (party, comm) = party.receive_precommitments(&precomms)?;
+                let comms = coordinator.submit_commitment(i, comm).await?;
+                let (party, share) = party.receive_commitments(&comms)?;
+                let shares = coordinator.submit_share(i, share).await?;
+                Ok::<_, MusigError>(party.receive_shares(&shares)?)
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        let signature = handle.await.unwrap().unwrap();
+        assert!(signature
+            .verify(
+                &mut Transcript::new(b"example transcript"),
+                multikey.aggregated_key()
+            )
+            .is_ok());
+    }
+
+    // A party cannot submit twice to the same round.
+    let precommitment = NoncePrecommitment::from_bytes([0u8; 32]);
+    assert_eq!(
+        coordinator.submit_precommitment(0, precommitment).await,
+        Err(MusigError::BadArguments)
+    );
+}
+
+#[test]
+fn rotate_key() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+
+    // The second participant rotates its key.
+    let rotated_privkeys = vec![privkeys[0], Scalar::from(4u64), privkeys[2]];
+    let old = VerificationKey::from_secret(&privkeys[1]);
+    let new = VerificationKey::from_secret(&rotated_privkeys[1]);
+    let rotated = multikey.rotate_key(&old, new).unwrap();
+    assert_eq!(
+        rotated.aggregated_key(),
+        multikey_helper(&rotated_privkeys).aggregated_key()
+    );
+    assert_ne!(rotated.aggregated_key(), multikey.aggregated_key());
+
+    // The new set signs for the rotated aggregate.
+    let transcript = Transcript::new(b"example transcript");
+    let (new_sig, _) =
+        sign_with_mpc(&rotated_privkeys, rotated.clone(), transcript.clone()).unwrap();
+    assert!(new_sig
+        .verify(&mut transcript.clone(), rotated.aggregated_key())
+        .is_ok());
+
+    // The old set's signatures do not verify against the rotated aggregate.
+    let (old_sig, _) = sign_with_mpc(&privkeys, multikey.clone(), transcript.clone()).unwrap();
+    assert!(old_sig
+        .verify(&mut transcript.clone(), rotated.aggregated_key())
+        .is_err());
+
+    // Rotating a key that is not in the multikey fails.
+    assert_eq!(
+        multikey.rotate_key(&new, old).err(),
+        Some(MusigError::BadArguments)
+    );
+}
+
+#[test]
+fn errors_compare_equal() {
+    // Errors with payloads can be cloned and compared in assertions.
+    let err = MusigError::InvalidKey { index: 2 };
+    assert_eq!(err.clone(), MusigError::InvalidKey { index: 2 });
+    assert_ne!(err, MusigError::InvalidKey { index: 1 });
+
+    let err = MusigError::from(StarsigError::InvalidSignature);
+    assert_eq!(
+        err.clone(),
+        MusigError::SignatureError(StarsigError::InvalidSignature)
+    );
+
+    let result: Result<(), MusigError> = Err(MusigError::InvalidPoint);
+    assert_eq!(result, Err(MusigError::InvalidPoint));
+}
+
+#[cfg(feature = "frost")]
+#[test]
+fn frost_threshold_signing() {
+    use crate::frost::{self, trusted_dealer_keygen, SigningNonces, SigningPackage};
+
+    let secret = Scalar::from(42u64);
+    let (shares, group_key) = trusted_dealer_keygen(secret, 2, 3, &mut rand::thread_rng()).unwrap();
+    assert_eq!(group_key, VerificationKey::from_secret(&secret));
+
+    // Any two of the three signers can sign.
+    for signers in &[[0, 1], [0, 2], [1, 2]] {
+        let message = b"example message";
+        let nonces: Vec<_> = signers
+            .iter()
+            .map(|i| SigningNonces::new(&shares[*i], &mut rand::thread_rng()))
+            .collect();
+        let commitments = signers
+            .iter()
+            .zip(&nonces)
+            .map(|(i, n)| n.commitment(shares[*i].identifier()))
+            .collect();
+        let package = SigningPackage::new(message, group_key, commitments).unwrap();
+
+        let signature_shares: Vec<_> = signers
+            .iter()
+            .zip(nonces)
+            .map(|(i, n)| shares[*i].sign(n, &package).unwrap())
+            .collect();
+        for (i, z_i) in signers.iter().zip(&signature_shares) {
+            let share = &shares[*i];
+            assert!(package
+                .verify_share(share.identifier(), *z_i, share.verification_key())
+                .is_ok());
+        }
+
+        // The aggregated signature is a FROST signature by the group key.
+        let signature = package.aggregate(&signature_shares).unwrap();
+        assert!(frost::verify(message, group_key, &signature).is_ok());
+        assert!(frost::verify(b"other message", group_key, &signature).is_err());
+    }
+}
+
+#[cfg(feature = "frost")]
+#[test]
+fn frost_rejects_bad_shares() {
+    use crate::frost::{
+        self, lagrange_coefficient, trusted_dealer_keygen, SigningNonces, SigningPackage,
+    };
+
+    let secret = Scalar::from(42u64);
+    let (shares, group_key) = trusted_dealer_keygen(secret, 2, 3, &mut rand::thread_rng()).unwrap();
+    let message = b"example message";
+    let nonces: Vec<_> = shares[..2]
+        .iter()
+        .map(|share| SigningNonces::new(share, &mut rand::thread_rng()))
+        .collect();
+    let commitments: Vec<_> = shares[..2]
+        .iter()
+        .zip(&nonces)
+        .map(|(share, n)| n.commitment(share.identifier()))
+        .collect();
+    let package = SigningPackage::new(message, group_key, commitments.clone()).unwrap();
+    let mut signature_shares: Vec<_> = shares[..2]
+        .iter()
+        .zip(nonces)
+        .map(|(share, n)| share.sign(n, &package).unwrap())
+        .collect();
+
+    // A tampered share is detected and attributed to its signer.
+    signature_shares[1] += Scalar::one();
+    let Y = shares[1].verification_key();
+    assert_eq!(
+        package.verify_share(shares[1].identifier(), signature_shares[1], Y),
+        Err(MusigError::ShareError {
+            pubkey: Y.to_bytes()
+        })
+    );
+    let signature = package.aggregate(&signature_shares).unwrap();
+    assert!(frost::verify(message, group_key, &signature).is_err());
+
+    // A signer that did not commit cannot sign.
+    let outsider = SigningNonces::new(&shares[2], &mut rand::thread_rng());
+    assert_eq!(
+        shares[2].sign(outsider, &package),
+        Err(MusigError::BadArguments)
+    );
+
+    // Repeated identifiers and invalid thresholds are rejected.
+    let repeated = vec![commitments[0], commitments[0]];
+    assert!(SigningPackage::new(message, group_key, repeated).is_err());
+    assert!(trusted_dealer_keygen(secret, 3, 2, &mut rand::thread_rng()).is_err());
+    assert!(trusted_dealer_keygen(secret, 0, 2, &mut rand::thread_rng()).is_err());
+
+    // The Lagrange coefficients interpolate the group key from any two verification shares.
+    let ids = [shares[0].identifier(), shares[2].identifier()];
+    let interpolated = shares[0]
+        .verification_key()
+        .into_point()
+        .decompress()
+        .unwrap()
+        * lagrange_coefficient(ids[0], &ids).unwrap()
+        + shares[2]
+            .verification_key()
+            .into_point()
+            .decompress()
+            .unwrap()
+            * lagrange_coefficient(ids[1], &ids).unwrap();
+    assert_eq!(interpolated.compress(), group_key.into_point());
+
+    // The coefficient is undefined for repeated, zero or missing identifiers.
+    assert_eq!(
+        lagrange_coefficient(1, &[1, 2, 2]),
+        Err(MusigError::BadArguments)
+    );
+    assert_eq!(
+        lagrange_coefficient(1, &[0, 1]),
+        Err(MusigError::BadArguments)
+    );
+    assert_eq!(
+        lagrange_coefficient(3, &[1, 2]),
+        Err(MusigError::BadArguments)
+    );
+}
+
+#[cfg(feature = "frost")]
+#[test]
+fn frost_rfc9591_vector() {
+    use crate::frost::{self, SecretShare, SigningNonces, SigningPackage};
+
+    // RFC 9591, Appendix E.4: FROST(ristretto255, SHA-512), signers 1 and 3 out of 3.
+    let scalar = |hex_str: &str| {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&hex::decode(hex_str).unwrap());
+        Scalar::from_canonical_bytes(bytes).unwrap()
+    };
+    let bytes32 = |hex_str: &str| {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&hex::decode(hex_str).unwrap());
+        bytes
+    };
+    let message = hex::decode("74657374").unwrap();
+    let group_key = VerificationKey::from_secret(&scalar(
+        "1b25a55e463cfd15cf14a5d3acc3d15053f08da49c8afcf3ab265f2ebc4f970b",
+    ));
+    assert_eq!(
+        hex::encode(group_key.as_bytes()),
+        "e2a62f39eede11269e3bd5a7d97554f5ca384f9f6d3dd9c3c0d05083c7254f57"
+    );
+
+    let signers = [
+        (
+            1,
+            "5c3430d391552f6e60ecdc093ff9f6f4488756aa6cebdbad75a768010b8f830e",
+            "f595a133b4d95c6e1f79887220c8b275ce6277e7f68a6640e1e7140f9be2fb5c",
+            "34dd1001360e3513cb37bebfabe7be4a32c5bb91ba19fbd4360d039111f0fbdc",
+            "965def4d0958398391fc06d8c2d72932608b1e6255226de4fb8d972dac15fd57",
+            "ec5170920660820007ae9e1d363936659ef622f99879898db86e5bf1d5bf2a14",
+            "9285f875923ce7e0c491a592e9ea1865ec1b823ead4854b48c8a46287749ee09",
+        ),
+        (
+            3,
+            "f17e505f0e2581c6acfe54d3846a622834b5e7b50cad9a2109a97ba7a80d5c04",
+            "daa0cf42a32617786d390e0c7edfbf2efbd428037069357b5173ae61d6dd5d5e",
+            "b4387e72b2e4108ce4168931cc2c7fcce5f345a5297368952c18b5fc8473f050",
+            "480e06e3de182bf83489c45d7441879932fd7b434a26af41455756264fbd5d6e",
+            "3064746dfd3c1862ef58fc68c706da287dd925066865ceacc816b3a28c7b363b",
+            "7cb211fe0e3d59d25db6e36b3fb32344794139602a7b24f1ae0dc4e26ad7b908",
+        ),
+    ];
+
+    let mut shares = Vec::new();
+    let mut nonces = Vec::new();
+    let mut commitments = Vec::new();
+    for (identifier, share, hiding_randomness, binding_randomness, hiding, binding, _) in &signers {
+        let share = SecretShare::new(*identifier, scalar(share)).unwrap();
+        let n = SigningNonces::from_randomness(
+            &share,
+            &bytes32(hiding_randomness),
+            &bytes32(binding_randomness),
+        );
+        let commitment = n.commitment(*identifier);
+        assert_eq!(hex::encode(commitment.hiding.as_bytes()), *hiding);
+        assert_eq!(hex::encode(commitment.binding.as_bytes()), *binding);
+        shares.push(share);
+        nonces.push(n);
+        commitments.push(commitment);
+    }
+
+    let package = SigningPackage::new(&message, group_key, commitments).unwrap();
+    let signature_shares: Vec<_> = shares
+        .iter()
+        .zip(nonces)
+        .zip(&signers)
+        .map(|((share, n), signer)| {
+            let z_i = share.sign(n, &package).unwrap();
+            assert_eq!(hex::encode(z_i.as_bytes()), signer.6);
+            assert!(package
+                .verify_share(share.identifier(), z_i, share.verification_key())
+                .is_ok());
+            z_i
+        })
+        .collect();
+
+    let signature = package.aggregate(&signature_shares).unwrap();
+    assert_eq!(
+        hex::encode(&signature.to_bytes()[..]),
+        "fc45655fbc66bbffad654ea4ce5fdae253a49a64ace25d9adb62010dd9fb2555\
+         2164141787162e5b4cab915b4aa45d94655dbb9ed7c378a53b980a0be220a802"
+    );
+    assert!(frost::verify(&message, group_key, &signature).is_ok());
+}
+
+#[test]
+fn weighted_quorum() {
+    let privkeys = [Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let weights = [5u64, 3, 2];
+    let keys_and_weights: Vec<_> = privkeys
+        .iter()
+        .zip(weights.iter())
+        .map(|(x, w)| (VerificationKey::from_secret(x), *w))
+        .collect();
+    let multikey = Multikey::new_weighted(&keys_and_weights).unwrap();
+
+    // The first two parties (weight 5 + 3 = 8) sign with the multikey of their subset.
+    let subset = Multikey::new_weighted(&keys_and_weights[..2]).unwrap();
+    let mut transcript = Transcript::new(b"Starsig.sign_message");
+    transcript.append_message(b"message", b"hello world");
+    let (signature, _) = sign_with_mpc(&privkeys[..2].to_vec(), subset, transcript).unwrap();
+    let signed = [true, true, false];
+
+    // The subset's weight just meets the threshold.
+    assert!(multikey
+        .verify_weighted_quorum(&signed, 8, b"message", b"hello world", &signature)
+        .is_ok());
+    // And just misses a higher one.
+    assert_eq!(
+        multikey.verify_weighted_quorum(&signed, 9, b"message", b"hello world", &signature),
+        Err(MusigError::QuorumNotMet {
+            weight: 8,
+            threshold: 9
+        })
+    );
+
+    // A bitmap claiming a different subset does not match the signature.
+    assert!(multikey
+        .verify_weighted_quorum(
+            &[true, true, true],
+            8,
+            b"message",
+            b"hello world",
+            &signature
+        )
+        .is_err());
+    assert_eq!(
+        multikey.verify_weighted_quorum(&[true, true], 8, b"message", b"hello world", &signature),
+        Err(MusigError::BadArguments)
+    );
+
+    // Weights summing past `u64::MAX` are rejected instead of wrapping around.
+    let heavy: Vec<_> = keys_and_weights
+        .iter()
+        .zip(&[u64::MAX, u64::MAX - 1, 2])
+        .map(|((X, _), w)| (*X, *w))
+        .collect();
+    let heavy = Multikey::new_weighted(&heavy).unwrap();
+    assert_eq!(
+        heavy.verify_weighted_quorum(
+            &[true, true, false],
+            8,
+            b"message",
+            b"hello world",
+            &signature
+        ),
+        Err(MusigError::BadArguments)
+    );
+    assert_eq!(
+        heavy.verify_weighted_quorum(
+            &[false, true, true],
+            8,
+            b"message",
+            b"hello world",
+            &signature
+        ),
+        Err(MusigError::BadArguments)
+    );
+}
+
+#[test]
+fn aggregation_proof() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let commitment = multikey.key_list_commitment();
+
+    assert!(multikey
+        .verify_key_list(multikey.aggregated_key(), &commitment)
+        .is_ok());
+
+    // A forged aggregate, e.g. the plain sum of the keys, is rejected.
+    let forged = VerificationKey::from_secret(&privkeys.iter().sum());
+    assert_eq!(
+        multikey.verify_key_list(forged, &commitment),
+        Err(MusigError::AggregatedKeyMismatch)
+    );
+
+    // A different (reordered) key list does not match the commitment.
+    let reordered = multikey_helper(&vec![privkeys[1], privkeys[0], privkeys[2]]);
+    assert_eq!(
+        reordered.verify_key_list(reordered.aggregated_key(), &commitment),
+        Err(MusigError::AggregatedKeyMismatch)
+    );
+
+    // Each key is checked against the commitment alone, and the proof survives encoding.
+    for (i, x) in privkeys.iter().enumerate() {
+        let proof = multikey.aggregation_proof(i).unwrap();
+        assert_eq!(proof.position(), i);
+        assert_eq!(proof.key(), VerificationKey::from_secret(x));
+        assert_eq!(proof.weight(), None);
+        assert!(proof.verify(&commitment).is_ok());
+
+        let decoded = AggregationProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(decoded.verify(&commitment).is_ok());
+
+        let other = reordered.aggregation_proof(i).unwrap();
+        if other.key() != proof.key() {
+            assert_eq!(
+                other.verify(&commitment),
+                Err(MusigError::AggregatedKeyMismatch)
+            );
+        }
+    }
+    assert!(multikey.aggregation_proof(3).is_none());
+
+    // Truncated or extended encodings are rejected.
+    let bytes = multikey.aggregation_proof(0).unwrap().to_bytes();
+    assert_eq!(
+        AggregationProof::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(MusigError::InvalidFormat)
+    );
+    assert_eq!(
+        AggregationProof::from_bytes(&[&bytes[..], &[0]].concat()),
+        Err(MusigError::InvalidFormat)
+    );
+
+    // Weighted multikeys commit to the weights too.
+    let keys_and_weights: Vec<_> = privkeys
+        .iter()
+        .map(|x| (VerificationKey::from_secret(x), 2))
+        .collect();
+    let weighted = Multikey::new_weighted(&keys_and_weights).unwrap();
+    let weighted_commitment = weighted.key_list_commitment();
+    assert_ne!(weighted_commitment, commitment);
+    assert!(weighted
+        .verify_key_list(weighted.aggregated_key(), &weighted_commitment)
+        .is_ok());
+    let weighted_proof = weighted.aggregation_proof(1).unwrap();
+    assert_eq!(weighted_proof.weight(), Some(2));
+    let decoded = AggregationProof::from_bytes(&weighted_proof.to_bytes()).unwrap();
+    assert!(decoded.verify(&weighted_commitment).is_ok());
+    assert!(decoded.verify(&commitment).is_err());
+}
+
+#[test]
+fn signer_path_returns_signing_errors() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let keys: Vec<_> = privkeys.iter().map(VerificationKey::from_secret).collect();
+    let mut transcripts: Vec<_> = privkeys
+        .iter()
+        .map(|_| Transcript::new(b"example transcript"))
+        .collect();
+    let (parties, precomms): (Vec<_>, Vec<_>) = privkeys
+        .iter()
+        .zip(transcripts.iter_mut())
+        .enumerate()
+        .map(|(i, (x_i, t))| Signer::new(t, i, *x_i, multikey.clone()))
+        .unzip();
+    let (mut parties, comms): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|p| p.receive_precommitments(&precomms).unwrap())
+        .unzip();
+
+    // A missing commitment.
+    assert_eq!(
+        parties.remove(0).receive_commitments(&comms[..2]).err(),
+        Some(SigningError::MissingParticipants {
+            expected: 3,
+            received: 2
+        })
+    );
+    // A commitment that does not open the precommitment.
+    let mut equivocated = comms.clone();
+    equivocated[1] = comms[2];
+    assert_eq!(
+        parties.remove(0).receive_commitments(&equivocated).err(),
+        Some(SigningError::Equivocation {
+            pubkey: keys[1].to_bytes()
+        })
+    );
+    // A commitment that is not a valid point.
+    let mut malformed = comms.clone();
+    malformed[0] = NonceCommitment::from_point(CompressedRistretto([0xff; 32]));
+    assert_eq!(
+        parties.remove(0).receive_commitments(&malformed).err(),
+        Some(SigningError::Aborted)
+    );
+
+    // The signing errors convert into `MusigError`, keeping the cause.
+    let err = MusigError::from(SigningError::Aborted);
+    assert_eq!(err, MusigError::ProtocolError(SigningError::Aborted));
+    assert_eq!(
+        std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<SigningError>())
+            .unwrap(),
+        &SigningError::Aborted
+    );
+}
+
+#[test]
+fn labeled_multikey() {
+    let keys: Vec<_> = (1..=3u64)
+        .map(|i| VerificationKey::from_secret(&Scalar::from(i)))
+        .collect();
+    let names = ["alice", "bob", "carol"];
+    let labeled = Multikey::new_labeled(
+        keys.iter()
+            .zip(names.iter())
+            .map(|(X, name)| (*X, name.to_string()))
+            .collect(),
+    )
+    .unwrap();
+    let unlabeled = Multikey::new(keys.clone()).unwrap();
+
+    assert_eq!(labeled.aggregated_key(), unlabeled.aggregated_key());
+    for (X, name) in keys.iter().zip(names.iter()) {
+        assert_eq!(labeled.label_for(X), Some(*name));
+        assert_eq!(unlabeled.label_for(X), None);
+    }
+    let stranger = VerificationKey::from_secret(&Scalar::from(4u64));
+    assert_eq!(labeled.label_for(&stranger), None);
+
+    // The rotated key takes over the label.
+    let rotated = labeled.rotate_key(&keys[1], stranger).unwrap();
+    assert_eq!(rotated.label_for(&stranger), Some("bob"));
+}
+
+#[test]
+fn fixed_multikey_validates_keys() {
+    let bad_key = VerificationKey::from_compressed(CompressedRistretto([0xff; 32]));
+    let key = VerificationKey::from_secret(&Scalar::from(1u64));
+
+    // A single key is validated like in `Multikey::new`, with the same error.
+    assert_eq!(
+        MultikeyN::new([bad_key]).err(),
+        Multikey::new(vec![bad_key]).err()
+    );
+    assert_eq!(
+        MultikeyN::new([key, bad_key]).err(),
+        Some(MusigError::InvalidKey { index: 1 })
+    );
+    assert!(MultikeyN::new([key]).is_ok());
+
+    // Duplicates are rejected and challenges match `Multikey`, with the same shared helpers.
+    let other = VerificationKey::from_secret(&Scalar::from(2u64));
+    assert_eq!(
+        MultikeyN::new([key, other, key]).err(),
+        Multikey::new(vec![key, other, key]).err()
+    );
+    let fixed = MultikeyN::new([key, other]).unwrap();
+    let multikey = Multikey::new(vec![key, other]).unwrap();
+    for i in 0..2 {
+        assert_eq!(
+            fixed.challenge(i, &mut Transcript::new(b"example transcript")),
+            multikey.challenge(i, &mut Transcript::new(b"example transcript"))
+        );
+    }
+}
+
+#[test]
+fn signer_state_dispatches_frames() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let mut transcripts: Vec<_> = privkeys
+        .iter()
+        .map(|_| Transcript::new(b"example transcript"))
+        .collect();
+
+    let (mut states, mut stream): (Vec<_>, Vec<u8>) = (Vec::new(), Vec::new());
+    for (i, (x_i, t)) in privkeys.iter().zip(transcripts.iter_mut()).enumerate() {
+        let (signer, precommitment) = Signer::new(t, i, *x_i, &multikey);
+        states.push(SignerState::AwaitingPrecommitments(signer));
+        stream.extend(SignerCodec::encode_message(&SignerMessage::Precommitment(
+            precommitment,
+        )));
+    }
+
+    // Each round, every party receives the frames of all the parties
+    // and sends its own frame for the next round.
+    for _ in 0..3 {
+        let mut next_stream = Vec::new();
+        states = states
+            .into_iter()
+            .map(|state| {
+                let (state, frame) = state.receive_frames(&stream).unwrap();
+                next_stream.extend(frame.unwrap_or_default());
+                state
+            })
+            .collect();
+        stream = next_stream;
+    }
+    assert!(stream.is_empty());
+
+    for state in states {
+        match state {
+            SignerState::Done(sig) => assert!(sig
+                .verify(
+                    &mut Transcript::new(b"example transcript"),
+                    multikey.aggregated_key()
+                )
+                .is_ok()),
+            _ => panic!("protocol is not complete"),
+        }
+    }
+
+    // Frames of a type not expected in the current state are rejected.
+    let mut t = Transcript::new(b"example transcript");
+    let (signer, _) = Signer::new(&mut t, 0, privkeys[0], &multikey);
+    let share = SignerCodec::encode_message(&SignerMessage::Share(Scalar::one()));
+    assert!(SignerState::AwaitingPrecommitments(signer)
+        .receive_frames(&share)
+        .is_err());
+}
+
+#[test]
+fn missing_precommitments_are_rejected() {
+    let privkeys = vec![Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let multikey = multikey_helper(&privkeys);
+    let mut transcripts: Vec<_> = privkeys
+        .iter()
+        .map(|_| Transcript::new(b"example transcript"))
+        .collect();
+    let (mut parties, precomms): (Vec<_>, Vec<_>) = transcripts
+        .iter_mut()
+        .zip(privkeys.iter())
+        .enumerate()
+        .map(|(i, (t, x_i))| Signer::new(t, i, *x_i, &multikey))
+        .unzip();
+    assert_eq!(
+        parties
+            .remove(0)
+            .receive_precommitments(&precomms[..2])
+            .err(),
+        Some(SigningError::MissingParticipants {
+            expected: 3,
+            received: 2
+        })
+    );
+
+    // Batch signers check the number of parties before regrouping the messages.
+    let mut batch_transcripts: Vec<_> = privkeys
+        .iter()
+        .map(|_| vec![Transcript::new(b"example transcript")])
+        .collect();
+    let (mut parties, precomms): (Vec<_>, Vec<_>) = batch_transcripts
+        .iter_mut()
+        .zip(privkeys.iter())
+        .enumerate()
+        .map(|(i, (t, x_i))| BatchSigner::new(t, i, *x_i, &multikey))
+        .unzip();
+    assert_eq!(
+        parties
+            .remove(0)
+            .receive_precommitments(&precomms[..2])
+            .err(),
+        Some(SigningError::MissingParticipants {
+            expected: 3,
+            received: 2
+        })
+    );
+    let short = vec![precomms[0].clone(), vec![], precomms[2].clone()];
+    assert_eq!(
+        parties.remove(0).receive_precommitments(&short).err(),
+        Some(SigningError::BatchSizeMismatch {
+            expected: 1,
+            received: 0
+        })
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[tracing_test::traced_test]
+#[test]
+fn tracing_spans_fixed_signer() {
+    let x = [Scalar::from(1u64), Scalar::from(2u64)];
+    let multikey = MultikeyN::new([
+        VerificationKey::from_secret(&x[0]),
+        VerificationKey::from_secret(&x[1]),
+    ])
+    .unwrap();
+
+    let mut t0 = Transcript::new(b"example transcript");
+    let mut t1 = Transcript::new(b"example transcript");
+    let (p0, precomm0) = SignerN::new(&mut t0, 0, x[0], multikey.clone());
+    let (p1, precomm1) = SignerN::new(&mut t1, 1, x[1], multikey);
+    let precomms = [precomm0, precomm1];
+    let (p0, comm0) = p0.receive_precommitments(&precomms);
+    let (p1, comm1) = p1.receive_precommitments(&precomms);
+    let comms = [comm0, comm1];
+    let (p0, share0) = p0.receive_commitments(&comms).unwrap();
+    let (_, share1) = p1.receive_commitments(&comms).unwrap();
+    p0.receive_shares(&[share0, share1]).unwrap();
+
+    for span in &[
+        "musig.new",
+        "musig.receive_precommitments",
+        "musig.receive_commitments",
+        "musig.receive_shares",
+    ] {
+        assert!(logs_contain(span));
+    }
+    assert!(logs_contain("position=1"));
+}
+
+#[cfg(feature = "frost")]
+#[test]
+fn sha512_vectors() {
+    use crate::sha512::Sha512;
+
+    let hash = |chunks: &[&[u8]]| {
+        let mut hasher = Sha512::new();
+        for chunk in chunks {
+            hasher.update(chunk);
+        }
+        hex::encode(&hasher.finalize()[..])
+    };
+
+    // FIPS 180-4 examples, including a message padded into a second block.
+    assert_eq!(
+        hash(&[]),
+        "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+         47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+    );
+    assert_eq!(
+        hash(&[b"abc"]),
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+         2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+    );
+    let two_blocks: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+        hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+    let expected = "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+                    501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909";
+    assert_eq!(hash(&[two_blocks]), expected);
+    // Splitting the input across updates does not change the hash.
+    assert_eq!(
+        hash(&[&two_blocks[..3], &two_blocks[3..100], &two_blocks[100..]]),
+        expected
+    );
+    assert_eq!(
+        hash(&[&[0x61; 1000][..]]),
+        hash(&[&[0x61; 500], &[0x61; 500]])
+    );
+}
@@ -0,0 +1,50 @@
+//! Optional instrumentation of the signing protocol with `tracing` spans,
+//! enabled with the `tracing` feature.
+
+#[cfg(feature = "tracing")]
+use merlin::Transcript;
+
+/// Returns a short digest of the transcript state, which identifies the signing session
+/// in the logs without revealing the transcript contents.
+#[cfg(feature = "tracing")]
+pub(crate) fn transcript_digest(transcript: &Transcript) -> String {
+    let mut digest = [0u8; 8];
+    transcript
+        .clone()
+        .challenge_bytes(b"trace-digest", &mut digest);
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Enters a span for the protocol step of the participant at `position`
+/// (or for the verification, which has no participant)
+/// until the end of the enclosing block, and emits an event within it.
+#[cfg(feature = "tracing")]
+macro_rules! protocol_span {
+    ($name:literal, $transcript:expr) => {
+        let _span = tracing::debug_span!(
+            $name,
+            transcript = %crate::trace::transcript_digest($transcript)
+        )
+        .entered();
+        tracing::debug!("enter {}", $name);
+    };
+    ($name:literal, $position:expr, $transcript:expr) => {
+        let _span = tracing::debug_span!(
+            $name,
+            position = $position,
+            transcript = %crate::trace::transcript_digest($transcript)
+        )
+        .entered();
+        tracing::debug!("enter {}", $name);
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! protocol_span {
+    ($name:literal, $transcript:expr) => {
+        let _ = &$transcript;
+    };
+    ($name:literal, $position:expr, $transcript:expr) => {
+        let _ = (&$position, &$transcript);
+    };
+}
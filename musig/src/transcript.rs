@@ -15,3 +15,12 @@ impl TranscriptProtocol for Transcript {
         self.append_u64(b"n", n as u64);
     }
 }
+
+/// Label of the transcript that aggregates the keys of a `Multikey`.
+pub const LABEL_AGGREGATION: &[u8] = b"Musig.aggregated-key";
+/// Label of the number of keys `n` committed to the aggregation transcript.
+pub const LABEL_KEY_COUNT: &[u8] = b"n";
+/// Label of the key index `i` committed before computing its coefficient.
+pub const LABEL_INDEX: &[u8] = b"i";
+/// Label of the per-key coefficient `a_i = H(<L>, X_i)`.
+pub const LABEL_COEFFICIENT: &[u8] = b"a_i";
@@ -0,0 +1,51 @@
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use starsig::Signature;
+
+use super::{Multikey, MusigError, NoncePrecommitment, Signer, SignerAwaitingPrecommitments};
+
+/// Bridge between a transaction transcript and the multi-party signing protocol.
+/// Each party creates its own `TxSigner` with the transaction transcript
+/// that already commits the transaction (e.g. its txid), and runs the protocol
+/// starting with `start`. The resulting signature is verified against the aggregated key.
+pub struct TxSigner {
+    tx_transcript: Transcript,
+    session_transcript: Transcript,
+    multikey: Multikey,
+}
+
+impl TxSigner {
+    /// Creates a signer for the transaction committed in `tx_transcript`.
+    pub fn new(tx_transcript: Transcript, multikey: Multikey) -> Self {
+        TxSigner {
+            session_transcript: tx_transcript.clone(),
+            tx_transcript,
+            multikey,
+        }
+    }
+
+    /// Starts the signing protocol for the party at `position` with the secret key `x_i`.
+    /// Every session signs over a fresh copy of the transaction transcript,
+    /// so `start` can be called again (e.g. to retry an aborted session),
+    /// and the signer states refer to the stored `Multikey` instead of a copy of it.
+    pub fn start(
+        &mut self,
+        position: usize,
+        x_i: Scalar,
+    ) -> (
+        SignerAwaitingPrecommitments<'_, &Multikey>,
+        NoncePrecommitment,
+    ) {
+        self.session_transcript = self.tx_transcript.clone();
+        Signer::new(&mut self.session_transcript, position, x_i, &self.multikey)
+    }
+
+    /// Verifies the transaction signature against the aggregated key.
+    pub fn verify(&self, signature: &Signature) -> Result<(), MusigError> {
+        signature.verify(
+            &mut self.tx_transcript.clone(),
+            self.multikey.aggregated_key(),
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+#[macro_use]
+extern crate criterion;
+use criterion::Criterion;
+
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand::thread_rng;
+
+use starsig::{BatchVerifier, Signature, VerificationKey};
+
+fn signed_messages(n: usize) -> Vec<(Signature, VerificationKey, Transcript)> {
+    let mut rng = thread_rng();
+    (0..n)
+        .map(|i| {
+            let privkey = Scalar::random(&mut rng);
+            let mut transcript = Transcript::new(b"Starsig.bench");
+            transcript.append_u64(b"i", i as u64);
+            let sig = Signature::sign(&mut transcript.clone(), privkey);
+            (sig, VerificationKey::from_secret(&privkey), transcript)
+        })
+        .collect()
+}
+
+fn verify_single(c: &mut Criterion) {
+    c.bench_function("Signature verification", move |b| {
+        let (sig, pubkey, transcript) = signed_messages(1).remove(0);
+        b.iter(|| {
+            sig.verify(&mut transcript.clone(), pubkey).unwrap();
+        })
+    });
+}
+
+fn verify_batch_helper(n: usize, c: &mut Criterion) {
+    let label = format!("Batch signature verification with {} signatures", n);
+
+    c.bench_function(&label, move |b| {
+        let signed = signed_messages(n);
+        b.iter(|| {
+            let mut batch = BatchVerifier::with_capacity(thread_rng(), n);
+            for (sig, pubkey, transcript) in signed.iter() {
+                sig.verify_batched(&mut transcript.clone(), *pubkey, &mut batch);
+            }
+            batch.verify().unwrap();
+        })
+    });
+}
+
+fn verify_batch_16(c: &mut Criterion) {
+    verify_batch_helper(16, c);
+}
+
+fn verify_batch_256(c: &mut Criterion) {
+    verify_batch_helper(256, c);
+}
+
+fn verify_batch_4096(c: &mut Criterion) {
+    verify_batch_helper(4096, c);
+}
+
+criterion_group! {
+    name = verify_signatures;
+    config = Criterion::default().sample_size(10);
+    targets = verify_single,
+        verify_batch_16,
+        verify_batch_256,
+        verify_batch_4096,
+}
+
+criterion_main!(verify_signatures);
@@ -0,0 +1,28 @@
+#ifndef STARSIG_H
+#define STARSIG_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Verifies the 64-byte signature at `sig` over the `msg_len` bytes at `msg`
+ * with the 32-byte verification key at `key`.
+ * The message must be signed with `Signature::sign_message` under the label "message".
+ *
+ * Returns 1 if the signature is valid, 0 if it is invalid,
+ * and -1 if the arguments are malformed (e.g. null pointers) or verification failed unexpectedly.
+ */
+int32_t starsig_verify_message(const uint8_t *sig,
+                               const uint8_t *key,
+                               const uint8_t *msg,
+                               size_t msg_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* STARSIG_H */
@@ -0,0 +1,6 @@
+#![deny(missing_docs)]
+//! C library exporting the `ffi` interface of Starsig.
+//! Builds `libstarsig_ffi.so` (`.dylib` on macOS) and `libstarsig_ffi.a`
+//! with the functions declared in `include/starsig.h`.
+
+pub use starsig::{starsig_verify_message, FFI_MESSAGE_LABEL};
@@ -0,0 +1,37 @@
+#![no_main]
+//! Splits the input into a 64-byte signature, a 32-byte verification key and a message,
+//! and checks that decoding and verification with `verify_simple` never panic
+//! and give the same result on every path.
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use libfuzzer_sys::fuzz_target;
+use starsig::{Signature, VerificationKey};
+
+const DOMAIN: &[u8] = b"Starsig.fuzz";
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < Signature::SIZE + VerificationKey::SIZE {
+        return;
+    }
+    let (sig_bytes, rest) = data.split_at(Signature::SIZE);
+    let (key_bytes, message) = rest.split_at(VerificationKey::SIZE);
+
+    let sig = match Signature::from_bytes(sig_bytes) {
+        Ok(sig) => sig,
+        Err(_) => return,
+    };
+    // Decoded signatures re-encode to the same bytes.
+    assert_eq!(&sig.to_bytes()[..], sig_bytes);
+
+    let mut key = [0u8; VerificationKey::SIZE];
+    key.copy_from_slice(key_bytes);
+    let key = VerificationKey::from_compressed(CompressedRistretto(key));
+
+    let result = sig.verify_simple(message, DOMAIN, key);
+    // Verification is deterministic, and matches the explicit transcript.
+    assert_eq!(result, sig.verify_simple(message, DOMAIN, key));
+    assert_eq!(
+        result,
+        sig.verify(&mut Signature::signing_transcript(DOMAIN, message), key)
+    );
+});
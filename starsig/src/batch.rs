@@ -8,6 +8,29 @@ use rand_core::{CryptoRng, RngCore};
 
 use super::errors::StarsigError;
 
+/// Compressed encoding of the Ristretto base point `B`, as given in the specification.
+pub(crate) const BASEPOINT_BYTES: [u8; 32] = [
+    0xe2, 0xf2, 0xae, 0x0a, 0x6a, 0xbc, 0x4e, 0x71, 0xa8, 0x84, 0xa9, 0x61, 0xc5, 0x00, 0x51, 0x5f,
+    0x58, 0xe3, 0x0b, 0x6a, 0xa5, 0x82, 0xdd, 0x8d, 0xb6, 0xa6, 0x59, 0x45, 0xe0, 0x8d, 0x2d, 0x76,
+];
+
+/// Checks that the base point provided by the curve library has the expected encoding,
+/// guarding against a miscompiled or tampered dependency.
+/// The check runs once per process in debug builds, and is a no-op in release builds.
+fn debug_assert_basepoint() {
+    #[cfg(debug_assertions)]
+    {
+        static CHECK: std::sync::Once = std::sync::Once::new();
+        CHECK.call_once(|| {
+            assert_eq!(
+                RISTRETTO_BASEPOINT_POINT.compress().to_bytes(),
+                BASEPOINT_BYTES,
+                "Ristretto base point has unexpected encoding"
+            );
+        });
+    }
+}
+
 /// Trait for a batch verification of signatures.
 /// If you are only verifying signatures, without other proofs, you can use
 /// concrete implementation `BatchVerifier` without rolling out your own.
@@ -49,6 +72,7 @@ impl BatchVerification for SingleVerifier {
         I::Item: Borrow<Scalar>,
         J: IntoIterator<Item = Option<RistrettoPoint>>,
     {
+        debug_assert_basepoint();
         self.result = RistrettoPoint::optional_multiscalar_mul(
             iter::once(basepoint_scalar).chain(dynamic_scalars),
             iter::once(Some(RISTRETTO_BASEPOINT_POINT)).chain(dynamic_points),
@@ -91,6 +115,7 @@ impl<R: RngCore + CryptoRng> BatchVerifier<R> {
 
     /// Performs the verification and returns the result.
     pub fn verify(self) -> Result<(), StarsigError> {
+        debug_assert_basepoint();
         let result = RistrettoPoint::optional_multiscalar_mul(
             iter::once(self.basepoint_scalar).chain(self.dyn_weights.into_iter()),
             iter::once(Some(RISTRETTO_BASEPOINT_POINT)).chain(self.dyn_points.into_iter()),
@@ -0,0 +1,136 @@
+//! Proofs of equality of discrete logs: `log_G(A) == log_H(B)`.
+//!
+//! The prover with the secret `x` such that `A = x·G` and `B = x·H` commits to a random `k`
+//! as `T_G = k·G` and `T_H = k·H`, makes the challenge `c = H(G, H, A, B, T_G, T_H)`
+//! from the transcript and responds with `z = k + c·x`, the same way as in a signature.
+//! The verifier checks `z·G == T_G + c·A` and `z·H == T_H + c·B`.
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use super::errors::StarsigError;
+use super::transcript::TranscriptProtocol;
+
+/// Proof that two points have the same discrete log with respect to two bases.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DleqProof {
+    T_G: CompressedRistretto,
+    T_H: CompressedRistretto,
+    z: Scalar,
+}
+
+impl DleqProof {
+    /// Size of the encoded proof in bytes.
+    pub const SIZE: usize = 96;
+
+    /// Encodes the proof as `T_G || T_H || z`.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut buf = [0u8; Self::SIZE];
+        buf[..32].copy_from_slice(self.T_G.as_bytes());
+        buf[32..64].copy_from_slice(self.T_H.as_bytes());
+        buf[64..].copy_from_slice(self.z.as_bytes());
+        buf
+    }
+
+    /// Decodes the proof from `T_G || T_H || z`.
+    /// Fails with `StarsigError::InvalidSignature` if the scalar `z` is not canonical.
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Result<DleqProof, StarsigError> {
+        let mut Tgbuf = [0u8; 32];
+        let mut Thbuf = [0u8; 32];
+        let mut zbuf = [0u8; 32];
+        Tgbuf[..].copy_from_slice(&bytes[..32]);
+        Thbuf[..].copy_from_slice(&bytes[32..64]);
+        zbuf[..].copy_from_slice(&bytes[64..]);
+        Ok(DleqProof {
+            T_G: CompressedRistretto(Tgbuf),
+            T_H: CompressedRistretto(Thbuf),
+            z: Scalar::from_canonical_bytes(zbuf).ok_or(StarsigError::InvalidSignature)?,
+        })
+    }
+}
+
+/// Proves that `A = x·G` and `B = x·H` have the same discrete log `x`.
+/// The transcript is advanced in place, like in `Signature::sign`,
+/// so the verifier must start from the same transcript state.
+pub fn prove(
+    transcript: &mut Transcript,
+    x: Scalar,
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+) -> DleqProof {
+    let A = (x * G).compress();
+    let B = (x * H).compress();
+
+    let mut rng = transcript
+        .build_rng()
+        .rekey_with_witness_bytes(b"x", x.as_bytes())
+        .finalize(&mut rand::thread_rng());
+
+    // Commit the blinding factor `k` with both bases.
+    let k = Scalar::random(&mut rng);
+    let T_G = (k * G).compress();
+    let T_H = (k * H).compress();
+    let c = challenge(transcript, G, H, &A, &B, &T_G, &T_H);
+
+    DleqProof {
+        T_G,
+        T_H,
+        z: k + c * x,
+    }
+}
+
+/// Verifies the proof that `A` and `B` have the same discrete log with respect to `G` and `H`.
+/// Transcript should be in the same state as it was during the `prove` call.
+pub fn verify(
+    transcript: &mut Transcript,
+    A: &RistrettoPoint,
+    B: &RistrettoPoint,
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    proof: &DleqProof,
+) -> Result<(), StarsigError> {
+    let T_G = proof
+        .T_G
+        .decompress()
+        .ok_or(StarsigError::InvalidSignature)?;
+    let T_H = proof
+        .T_H
+        .decompress()
+        .ok_or(StarsigError::InvalidSignature)?;
+    let c = challenge(
+        transcript,
+        G,
+        H,
+        &A.compress(),
+        &B.compress(),
+        &proof.T_G,
+        &proof.T_H,
+    );
+
+    if proof.z * G == T_G + c * A && proof.z * H == T_H + c * B {
+        Ok(())
+    } else {
+        Err(StarsigError::InvalidSignature)
+    }
+}
+
+/// Makes the challenge `c = H(G, H, A, B, T_G, T_H)`.
+fn challenge(
+    transcript: &mut Transcript,
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    A: &CompressedRistretto,
+    B: &CompressedRistretto,
+    T_G: &CompressedRistretto,
+    T_H: &CompressedRistretto,
+) -> Scalar {
+    transcript.append_message(b"dom-sep", b"starsig dleq");
+    transcript.append_point(b"G", &G.compress());
+    transcript.append_point(b"H", &H.compress());
+    transcript.append_point(b"A", A);
+    transcript.append_point(b"B", B);
+    transcript.append_point(b"T_G", T_G);
+    transcript.append_point(b"T_H", T_H);
+    transcript.challenge_scalar(b"c")
+}
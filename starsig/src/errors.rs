@@ -9,4 +9,26 @@ pub enum StarsigError {
     /// This error occurs when a set of signatures failed to verify as a batch
     #[error("Batch signature verification failed")]
     InvalidBatch,
+
+    /// This error occurs when a signature is verified against the identity key
+    #[error("Verification key is invalid")]
+    InvalidKey,
+
+    /// This error occurs when a strict transcript is used for signing or verification
+    /// before any message was committed to it
+    #[error("Message was not committed to the transcript")]
+    MessageNotCommitted,
+
+    /// This error occurs when a message is verified under an empty domain-separation label,
+    /// which provides no separation from other protocols
+    #[error("Domain-separation label is empty")]
+    EmptyDomain,
+
+    /// This error occurs when a serialized `TranscriptState` is malformed
+    #[error("Transcript state encoding is invalid")]
+    InvalidTranscriptState,
+
+    /// This error occurs when a signature reuses a nonce commitment recorded in a `NonceLedger`
+    #[error("Nonce commitment was already used")]
+    NonceReplayed,
 }
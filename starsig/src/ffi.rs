@@ -0,0 +1,70 @@
+//! C interface for message verification, enabled with the `ffi` feature.
+//!
+//! The `starsig-ffi` crate in the `ffi` directory builds it as a C library
+//! (`libstarsig_ffi.a` and `libstarsig_ffi.so`) and declares the function in `include/starsig.h`:
+//!
+//! ```c
+//! int32_t starsig_verify_message(const uint8_t *sig,
+//!                                const uint8_t *key,
+//!                                const uint8_t *msg,
+//!                                size_t msg_len);
+//! ```
+//!
+//! ```text
+//! cargo build --release -p starsig-ffi
+//! cc app.c -I starsig/ffi/include -L target/release -l:libstarsig_ffi.a -lpthread -ldl -lm
+//! ```
+
+use core::slice;
+use std::panic;
+
+use super::key::VerificationKey;
+use super::signature::Signature;
+use curve25519_dalek::ristretto::CompressedRistretto;
+
+/// Label under which `starsig_verify_message` expects the message to be signed
+/// with `Signature::sign_message`.
+pub const FFI_MESSAGE_LABEL: &[u8] = b"message";
+
+/// Verifies the 64-byte signature at `sig_ptr` over the `msg_len` bytes at `msg_ptr`
+/// with the 32-byte verification key at `key_ptr`.
+/// The message must be signed with `Signature::sign_message` under `FFI_MESSAGE_LABEL`.
+///
+/// Returns 1 if the signature is valid, 0 if it is invalid,
+/// and -1 if the arguments are malformed (e.g. null pointers) or verification failed unexpectedly.
+///
+/// # Safety
+///
+/// `sig_ptr` and `key_ptr` must point to 64 and 32 readable bytes respectively,
+/// and `msg_ptr` must point to `msg_len` readable bytes (or be null if `msg_len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn starsig_verify_message(
+    sig_ptr: *const u8,
+    key_ptr: *const u8,
+    msg_ptr: *const u8,
+    msg_len: usize,
+) -> i32 {
+    if sig_ptr.is_null() || key_ptr.is_null() || (msg_ptr.is_null() && msg_len > 0) {
+        return -1;
+    }
+    let sig = slice::from_raw_parts(sig_ptr, Signature::SIZE);
+    let mut key = [0u8; 32];
+    key.copy_from_slice(slice::from_raw_parts(key_ptr, 32));
+    let msg = if msg_len == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(msg_ptr, msg_len)
+    };
+
+    let result = panic::catch_unwind(|| {
+        let pubkey = VerificationKey::from_compressed(CompressedRistretto(key));
+        Signature::from_bytes(sig)
+            .and_then(|sig| sig.verify_message(FFI_MESSAGE_LABEL, msg, pubkey))
+            .is_ok()
+    });
+    match result {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(_) => -1,
+    }
+}
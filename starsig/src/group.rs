@@ -0,0 +1,204 @@
+use core::ops::{Add, Mul};
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand::{CryptoRng, RngCore};
+
+use super::errors::StarsigError;
+use super::key::VerificationKey;
+use super::signature::Signature;
+use super::transcript::{TranscriptProtocol, LABEL_CHALLENGE, LABEL_KEY, LABEL_NONCE};
+
+/// Prime-order group over which the Schnorr signature protocol can be run,
+/// so that the protocol does not need to be duplicated to support another group.
+pub trait Group {
+    /// Scalar field element.
+    type Scalar: Copy + PartialEq + Add<Output = Self::Scalar> + Mul<Output = Self::Scalar>;
+    /// Group element.
+    type Point: Copy + PartialEq + Add<Output = Self::Point>;
+
+    /// Returns the generator `G` of the group.
+    fn basepoint() -> Self::Point;
+
+    /// Multiplies the `point` by the `scalar`.
+    fn mul(scalar: &Self::Scalar, point: &Self::Point) -> Self::Point;
+
+    /// Generates a random scalar.
+    fn random_scalar<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Scalar;
+
+    /// Encodes the scalar, for binding the nonce derivation to the private key.
+    fn scalar_bytes(scalar: &Self::Scalar) -> Vec<u8>;
+
+    /// Commits the `point` to the transcript with the given `label`.
+    fn append_point(transcript: &mut Transcript, label: &'static [u8], point: &Self::Point);
+
+    /// Computes a `label`ed challenge scalar.
+    fn challenge_scalar(transcript: &mut Transcript, label: &'static [u8]) -> Self::Scalar;
+
+    /// Returns true if the `point` is the identity, which is rejected as a verification key.
+    /// The default relies on the identity being the only point `P` with `P + P == P`.
+    fn is_identity(point: &Self::Point) -> bool {
+        *point + *point == *point
+    }
+
+    /// Creates the signature for `GroupSignature::sign`.
+    /// Groups with a dedicated implementation of the protocol override this
+    /// together with `verify`; the default runs the protocol over the group operations.
+    fn sign(transcript: &mut Transcript, privkey: Self::Scalar) -> GroupSignature<Self>
+    where
+        Self: Sized,
+    {
+        GroupSignature::sign_generic(transcript, privkey)
+    }
+
+    /// Verifies the signature for `GroupSignature::verify`, see `sign`.
+    fn verify(
+        sig: &GroupSignature<Self>,
+        transcript: &mut Transcript,
+        X: Self::Point,
+    ) -> Result<(), StarsigError>
+    where
+        Self: Sized,
+    {
+        sig.verify_generic(transcript, X)
+    }
+}
+
+/// The Ristretto group used by `Signature`.
+/// Signatures over it are made and verified by `Signature`.
+pub struct Ristretto;
+
+impl Group for Ristretto {
+    type Scalar = Scalar;
+    type Point = RistrettoPoint;
+
+    fn basepoint() -> RistrettoPoint {
+        RISTRETTO_BASEPOINT_POINT
+    }
+
+    fn mul(scalar: &Scalar, point: &RistrettoPoint) -> RistrettoPoint {
+        scalar * point
+    }
+
+    fn random_scalar<R: RngCore + CryptoRng>(rng: &mut R) -> Scalar {
+        Scalar::random(rng)
+    }
+
+    fn scalar_bytes(scalar: &Scalar) -> Vec<u8> {
+        scalar.to_bytes().to_vec()
+    }
+
+    fn append_point(transcript: &mut Transcript, label: &'static [u8], point: &RistrettoPoint) {
+        transcript.append_point(label, &point.compress());
+    }
+
+    fn challenge_scalar(transcript: &mut Transcript, label: &'static [u8]) -> Scalar {
+        TranscriptProtocol::challenge_scalar(transcript, label)
+    }
+
+    fn sign(transcript: &mut Transcript, privkey: Scalar) -> GroupSignature<Self> {
+        let sig = Signature::sign(transcript, privkey);
+        GroupSignature {
+            s: sig.s,
+            R: sig
+                .R
+                .decompress()
+                .expect("The nonce commitment is a valid point."),
+        }
+    }
+
+    fn verify(
+        sig: &GroupSignature<Self>,
+        transcript: &mut Transcript,
+        X: RistrettoPoint,
+    ) -> Result<(), StarsigError> {
+        Signature::from(sig).verify(transcript, VerificationKey::from(X))
+    }
+}
+
+/// A Schnorr signature over the group `G`, made and verified like `Signature`:
+/// `s = r + c·x` with `R = r·G` and `c = H(X, R, m)`.
+/// Over `Ristretto` it is `Signature` with a decompressed nonce commitment,
+/// and is signed and verified by `Signature`'s implementation.
+pub struct GroupSignature<G: Group = Ristretto> {
+    /// Signature using nonce, message, and private key
+    pub s: G::Scalar,
+    /// Nonce commitment
+    pub R: G::Point,
+}
+
+impl<G: Group> GroupSignature<G> {
+    /// Creates a signature for a single private key and single message.
+    pub fn sign(transcript: &mut Transcript, privkey: G::Scalar) -> Self {
+        G::sign(transcript, privkey)
+    }
+
+    /// Verifies the signature over a transcript by the key `X`: `s·G == R + c·X`.
+    /// Transcript should be in the same state as it was during the `sign` call
+    /// that created the signature.
+    /// Verification against the identity fails with `StarsigError::InvalidKey`, like in `Signature::verify`.
+    pub fn verify(&self, transcript: &mut Transcript, X: G::Point) -> Result<(), StarsigError> {
+        G::verify(self, transcript, X)
+    }
+
+    fn sign_generic(transcript: &mut Transcript, privkey: G::Scalar) -> Self {
+        let mut rng = transcript
+            .build_rng()
+            .rekey_with_witness_bytes(b"x", &G::scalar_bytes(&privkey))
+            .finalize(&mut rand::thread_rng());
+
+        // Generate ephemeral keypair (r, R). r is a random nonce.
+        let r = G::random_scalar(&mut rng);
+        let R = G::mul(&r, &G::basepoint());
+        let X = G::mul(&privkey, &G::basepoint());
+
+        let c = Self::challenge(transcript, &X, &R);
+        GroupSignature {
+            s: r + c * privkey,
+            R,
+        }
+    }
+
+    fn verify_generic(&self, transcript: &mut Transcript, X: G::Point) -> Result<(), StarsigError> {
+        if G::is_identity(&X) {
+            return Err(StarsigError::InvalidKey);
+        }
+        let c = Self::challenge(transcript, &X, &self.R);
+        if G::mul(&self.s, &G::basepoint()) == self.R + G::mul(&c, &X) {
+            Ok(())
+        } else {
+            Err(StarsigError::InvalidSignature)
+        }
+    }
+
+    /// Makes c = H(X, R, m), with the same labels as `Signature`.
+    /// The message has already been fed into the transcript.
+    fn challenge(transcript: &mut Transcript, X: &G::Point, R: &G::Point) -> G::Scalar {
+        transcript.starsig_domain_sep();
+        G::append_point(transcript, LABEL_KEY, X);
+        G::append_point(transcript, LABEL_NONCE, R);
+        G::challenge_scalar(transcript, LABEL_CHALLENGE)
+    }
+}
+
+impl GroupSignature<Ristretto> {
+    /// Converts the `Signature` for verification over the `Ristretto` group.
+    /// Fails with `StarsigError::InvalidSignature` if `R` is not a valid point.
+    pub fn from_signature(sig: &Signature) -> Result<Self, StarsigError> {
+        Ok(GroupSignature {
+            s: sig.s,
+            R: sig.R.decompress().ok_or(StarsigError::InvalidSignature)?,
+        })
+    }
+}
+
+impl From<&GroupSignature<Ristretto>> for Signature {
+    fn from(sig: &GroupSignature<Ristretto>) -> Self {
+        Signature {
+            s: sig.s,
+            R: sig.R.compress(),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand::{CryptoRng, RngCore};
+
+use super::errors::StarsigError;
+use super::key::VerificationKey;
+
+/// Prover in the interactive Schnorr identification protocol:
+/// the prover commits to a nonce `R = r·G`, the verifier replies with a random challenge `c`,
+/// and the prover responds with `s = r + c·x`, which the verifier checks with `verify_id`.
+/// This is the same equation as in a signature, with the challenge chosen by the verifier
+/// instead of derived from a transcript.
+pub struct IdProver {
+    privkey: Scalar,
+    r: Scalar,
+}
+
+impl IdProver {
+    /// Creates a prover for the `privkey`, generating the nonce from the `rng` and the key,
+    /// so that a weak `rng` alone does not reveal the nonce.
+    pub fn new<R: RngCore + CryptoRng>(privkey: Scalar, rng: &mut R) -> Self {
+        let mut rng = Transcript::new(b"Starsig.identification")
+            .build_rng()
+            .rekey_with_witness_bytes(b"x", privkey.as_bytes())
+            .finalize(rng);
+        IdProver {
+            privkey,
+            r: Scalar::random(&mut rng),
+        }
+    }
+
+    /// Returns the nonce commitment `R = r·G` to be sent to the verifier.
+    pub fn commit(&self) -> CompressedRistretto {
+        (RISTRETTO_BASEPOINT_POINT * self.r).compress()
+    }
+
+    /// Responds to the verifier's challenge `c` with `s = r + c·x`.
+    /// Consumes the prover, so that the nonce is never used with two challenges,
+    /// which would reveal the private key.
+    pub fn respond(self, c: Scalar) -> Scalar {
+        self.r + c * self.privkey
+    }
+}
+
+/// Verifies the prover's response `s` to the challenge `c` for the commitment `R`
+/// by the key `X`: `s·G == R + c·X`.
+/// The challenge must be chosen at random by the verifier after receiving `R`.
+pub fn verify_id(
+    X: VerificationKey,
+    R: CompressedRistretto,
+    c: Scalar,
+    s: Scalar,
+) -> Result<(), StarsigError> {
+    if X == VerificationKey::IDENTITY {
+        return Err(StarsigError::InvalidKey);
+    }
+    let X = X.as_point().decompress().ok_or(StarsigError::InvalidKey)?;
+    let R = R.decompress().ok_or(StarsigError::InvalidSignature)?;
+    if RISTRETTO_BASEPOINT_POINT * s == R + c * X {
+        Ok(())
+    } else {
+        Err(StarsigError::InvalidSignature)
+    }
+}
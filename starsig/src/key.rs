@@ -1,11 +1,19 @@
-use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use core::cmp::Ordering;
+use curve25519_dalek::constants::{BASEPOINT_ORDER, RISTRETTO_BASEPOINT_POINT};
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConditionallySelectable};
 
 /// Signing key (aka "privkey") is a type alias for the scalar in Ristretto255 group.
 pub type SigningKey = Scalar;
 
+/// Private key generated with `PrivateKey::generate`: a uniformly random non-zero scalar.
+#[derive(Clone)]
+pub struct PrivateKey(Scalar);
+
 /// Verification key (aka "pubkey") is a wrapper type around a Ristretto point
 /// that lets the verifier to check the signature.
 #[derive(Copy, Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
@@ -15,6 +23,15 @@ pub struct VerificationKey {
 }
 
 impl VerificationKey {
+    /// Size of the encoded verification key in bytes.
+    pub const SIZE: usize = 32;
+
+    /// Identity point as a verification key. Useful for initialization,
+    /// but signatures never verify against it.
+    pub const IDENTITY: VerificationKey = VerificationKey {
+        point: CompressedRistretto([0u8; 32]),
+    };
+
     /// Constructs a VerificationKey from a private key.
     pub fn from_secret(privkey: &Scalar) -> Self {
         Self::from_secret_decompressed(privkey).into()
@@ -41,14 +58,53 @@ impl VerificationKey {
     }
 
     /// Returns the view into byte representation of the verification key
-    pub fn as_bytes(&self) -> &[u8; 32] {
+    pub fn as_bytes(&self) -> &[u8; Self::SIZE] {
         self.point.as_bytes()
     }
 
     /// Returns the byte representation of the verification key
-    pub fn to_bytes(&self) -> [u8; 32] {
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
         self.point.to_bytes()
     }
+
+    /// Checks that the key decompresses to a point in the prime-order subgroup.
+    /// Valid Ristretto points are always torsion-free, so this only fails for keys
+    /// that do not decompress, or if the curve library misbehaves.
+    pub fn is_torsion_free(&self) -> bool {
+        match self.point.decompress() {
+            Some(point) => (point * BASEPOINT_ORDER).is_identity(),
+            None => false,
+        }
+    }
+}
+
+impl PrivateKey {
+    /// Generates a private key uniformly at random among the non-zero scalars.
+    /// Candidates are sampled as random 253-bit integers, and rejected if they are
+    /// not below the group order or zero, so the result has no modular bias.
+    pub fn generate<R: CryptoRng + RngCore>(rng: &mut R) -> PrivateKey {
+        loop {
+            let mut bytes = [0u8; 32];
+            rng.fill_bytes(&mut bytes);
+            // The group order is slightly above 2^252, so about half of the candidates are accepted.
+            bytes[31] &= 0x1f;
+            if let Some(scalar) = Scalar::from_canonical_bytes(bytes) {
+                if scalar != Scalar::zero() {
+                    return PrivateKey(scalar);
+                }
+            }
+        }
+    }
+
+    /// Returns the signing key scalar.
+    pub fn as_scalar(&self) -> &SigningKey {
+        &self.0
+    }
+
+    /// Returns the verification key of the private key.
+    pub fn verification_key(&self) -> VerificationKey {
+        VerificationKey::from_secret(&self.0)
+    }
 }
 
 impl From<RistrettoPoint> for VerificationKey {
@@ -70,3 +126,31 @@ impl Into<CompressedRistretto> for VerificationKey {
         self.into_point()
     }
 }
+
+/// Selects between two keys in constant time, byte by byte of their compressed encoding,
+/// so that the choice of a key does not branch on a secret condition.
+impl ConditionallySelectable for VerificationKey {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::conditional_select(&a.point.0[i], &b.point.0[i], choice);
+        }
+        VerificationKey {
+            point: CompressedRistretto(bytes),
+        }
+    }
+}
+
+/// Keys are ordered lexicographically by their compressed encoding,
+/// so that sets of keys can be sorted deterministically.
+impl Ord for VerificationKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl PartialOrd for VerificationKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::BTreeSet;
+
+use super::errors::StarsigError;
+use super::key::VerificationKey;
+use super::signature::Signature;
+use super::transcript::TranscriptProtocol;
+
+/// Record of the nonce commitments `R` of the verified signatures,
+/// for applications that treat each `R` as a one-time token.
+/// Any signature with an already recorded `R` is rejected, whatever the message or the key,
+/// including the same signature verified again.
+#[derive(Clone, Debug, Default)]
+pub struct NonceLedger {
+    seen: BTreeSet<[u8; 32]>,
+}
+
+impl NonceLedger {
+    /// Creates an empty ledger.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verifies the signature like `Signature::verify`, and records its `R`.
+    /// Fails with `StarsigError::NonceReplayed` if the `R` was already recorded.
+    /// The `R` of an invalid signature is not recorded, so a forged signature
+    /// cannot use up the nonce of a legitimate one.
+    pub fn verify_once(
+        &mut self,
+        sig: &Signature,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        if self.seen.contains(sig.R.as_bytes()) {
+            return Err(StarsigError::NonceReplayed);
+        }
+        sig.verify(transcript, pubkey)?;
+        self.seen.insert(sig.R.to_bytes());
+        Ok(())
+    }
+
+    /// Returns the number of recorded nonce commitments.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Returns true if no nonce commitments were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
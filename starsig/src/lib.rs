@@ -3,17 +3,43 @@
 //! Schnorr signature implementation.
 
 mod batch;
+pub mod dleq;
 mod errors;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod group;
+mod identification;
 mod key;
+mod ledger;
+mod message;
+mod nonce;
+mod proof;
+mod report;
 mod serialization;
 mod signature;
+mod state;
 mod transcript;
+mod witness;
 
 #[cfg(test)]
 mod tests;
 
 pub use self::batch::{BatchVerification, BatchVerifier, SingleVerifier};
 pub use self::errors::StarsigError;
-pub use self::key::{SigningKey, VerificationKey};
+#[cfg(feature = "ffi")]
+pub use self::ffi::{starsig_verify_message, FFI_MESSAGE_LABEL};
+pub use self::group::{Group, GroupSignature, Ristretto};
+pub use self::identification::{verify_id, IdProver};
+pub use self::key::{PrivateKey, SigningKey, VerificationKey};
+pub use self::ledger::NonceLedger;
+pub use self::message::{MessageBuilder, PrehashedMessage, StrictTranscript};
+pub use self::nonce::DeterministicNonce;
+pub use self::proof::SignatureProof;
+pub use self::report::{VerifyFailure, VerifyOutcome, Warning};
 pub use self::signature::Signature;
-pub use self::transcript::TranscriptProtocol;
+pub use self::state::TranscriptState;
+pub use self::transcript::{
+    ChallengeLayout, TranscriptProtocol, DOMAIN_SEP, LABEL_CHALLENGE, LABEL_DOMAIN_SEP, LABEL_KEY,
+    LABEL_NONCE,
+};
+pub use self::witness::VerifyWitness;
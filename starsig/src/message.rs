@@ -0,0 +1,74 @@
+use merlin::Transcript;
+
+use super::errors::StarsigError;
+use super::transcript::TranscriptProtocol;
+
+/// A 32-byte digest of a message that was hashed by the caller.
+/// Prehashed messages are committed under a distinct transcript label,
+/// so a signature over a prehash is never valid for a raw message with the same bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PrehashedMessage(pub [u8; 32]);
+
+/// Builder of a transcript for a message composed of several labeled fields.
+/// Signer and verifier must commit the same fields in the same order:
+/// omitting, reordering or relabeling a field produces a different transcript.
+#[derive(Clone)]
+pub struct MessageBuilder {
+    transcript: Transcript,
+}
+
+impl MessageBuilder {
+    /// Creates a new builder with a transcript labeled with a given domain-separation `label`.
+    pub fn new(label: &'static [u8]) -> Self {
+        MessageBuilder {
+            transcript: Transcript::new(label),
+        }
+    }
+
+    /// Commits a field `value` under the given `label`.
+    pub fn field(mut self, label: &'static [u8], value: &[u8]) -> Self {
+        self.transcript.commit_field(label, value);
+        self
+    }
+
+    /// Returns the transcript bound to all the committed fields,
+    /// ready to be used with `Signature::sign` and `Signature::verify`.
+    pub fn into_transcript(self) -> Transcript {
+        self.transcript
+    }
+}
+
+/// Transcript wrapper for the strict mode that tracks whether a message was committed.
+/// Signing and verification with `Signature::sign_strict_transcript` and
+/// `Signature::verify_strict_transcript` fail with `StarsigError::MessageNotCommitted`
+/// if no message was committed, instead of silently signing the bare transcript.
+#[derive(Clone)]
+pub struct StrictTranscript {
+    transcript: Transcript,
+    message_committed: bool,
+}
+
+impl StrictTranscript {
+    /// Creates a new transcript with a given domain-separation `label`.
+    pub fn new(label: &'static [u8]) -> Self {
+        StrictTranscript {
+            transcript: Transcript::new(label),
+            message_committed: false,
+        }
+    }
+
+    /// Commits a message under the given `label` and marks the transcript as committed.
+    pub fn commit_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.transcript.commit_field(label, message);
+        self.message_committed = true;
+    }
+
+    /// Returns the underlying transcript if a message was committed to it.
+    pub(crate) fn committed(&mut self) -> Result<&mut Transcript, StarsigError> {
+        if self.message_committed {
+            Ok(&mut self.transcript)
+        } else {
+            Err(StarsigError::MessageNotCommitted)
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use super::transcript::TranscriptProtocol;
+
+/// Deterministic nonce derivation for devices without a reliable RNG.
+/// The nonce is derived from the message transcript, the secret key,
+/// a per-device secret `seed` and a `counter` maintained by the caller.
+///
+/// The caller must never reuse the counter with the same seed and key
+/// (e.g. by persisting it across reboots): the counter is committed into
+/// the nonce derivation, so each signature gets a distinct nonce even for the same message.
+#[derive(Clone)]
+pub struct DeterministicNonce {
+    seed: [u8; 32],
+    counter: u64,
+}
+
+impl DeterministicNonce {
+    /// Creates a nonce source from a per-device secret seed and a counter.
+    pub fn new(seed: [u8; 32], counter: u64) -> Self {
+        DeterministicNonce { seed, counter }
+    }
+
+    /// Derives the nonce `r` for the given message transcript and secret key.
+    /// The message has already been fed into the transcript.
+    pub(crate) fn derive(&self, transcript: &Transcript, privkey: &Scalar) -> Scalar {
+        let mut t = transcript.clone();
+        t.append_message(b"dom-sep", b"Starsig.deterministic-nonce");
+        t.append_message(b"x", privkey.as_bytes());
+        t.append_message(b"seed", &self.seed);
+        t.append_u64(b"counter", self.counter);
+        t.challenge_scalar(b"r")
+    }
+}
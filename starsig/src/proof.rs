@@ -0,0 +1,120 @@
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use super::errors::StarsigError;
+use super::key::VerificationKey;
+use super::signature::Signature;
+use super::transcript::{ChallengeLayout, TranscriptProtocol, LABEL_KEY, LABEL_NONCE};
+
+/// Non-interactive proof of possession of a valid signature, that does not reveal the scalar `s`.
+///
+/// The verification equation `s·G == R + c·X` is rewritten as `s·G == P` with `P = R + c·X`,
+/// and the proof is a Schnorr proof of knowledge of the discrete log `s` of `P`.
+/// The nonce commitment `R` is part of the proof: the challenge `c = H(X, R, m)` is a hash of `R`,
+/// which a sigma protocol cannot prove without revealing `R` to the verifier.
+/// Without `s`, the proof cannot be turned back into the signature.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SignatureProof {
+    R: CompressedRistretto,
+    T: CompressedRistretto,
+    z: Scalar,
+}
+
+impl SignatureProof {
+    /// Size of the encoded proof in bytes.
+    pub const SIZE: usize = 96;
+
+    /// Proves possession of the signature `sig` over the `transcript` by the key `X`.
+    /// Fails with `StarsigError::InvalidSignature` if the signature does not verify.
+    pub fn prove(
+        sig: &Signature,
+        transcript: &Transcript,
+        X: VerificationKey,
+    ) -> Result<SignatureProof, StarsigError> {
+        sig.verify(&mut transcript.clone(), X)?;
+
+        let mut t = transcript.clone();
+        let mut rng = t
+            .build_rng()
+            .rekey_with_witness_bytes(b"s", sig.s.as_bytes())
+            .finalize(&mut rand::thread_rng());
+
+        // Commit the blinding factor `k` as `T = k·G`.
+        let k = Scalar::random(&mut rng);
+        let T = (RISTRETTO_BASEPOINT_POINT * k).compress();
+        let e = Self::challenge(&mut t, &X, &sig.R, &T);
+
+        Ok(SignatureProof {
+            R: sig.R,
+            T,
+            z: k + e * sig.s,
+        })
+    }
+
+    /// Verifies the proof of possession of a signature over the `transcript` by the key `X`.
+    pub fn verify(&self, transcript: &Transcript, X: VerificationKey) -> Result<(), StarsigError> {
+        if X == VerificationKey::IDENTITY {
+            return Err(StarsigError::InvalidKey);
+        }
+        let R = self.R.decompress().ok_or(StarsigError::InvalidSignature)?;
+        let T = self.T.decompress().ok_or(StarsigError::InvalidSignature)?;
+        let X_point = X
+            .into_point()
+            .decompress()
+            .ok_or(StarsigError::InvalidKey)?;
+
+        // P = R + c·X is the point whose discrete log is the signature scalar `s`.
+        let c = ChallengeLayout::KeyFirst.challenge(&mut transcript.clone(), X.as_point(), &self.R);
+        let P = R + c * X_point;
+
+        // z·G == T + e·P
+        let e = Self::challenge(&mut transcript.clone(), &X, &self.R, &self.T);
+        if RISTRETTO_BASEPOINT_POINT * self.z == T + e * P {
+            Ok(())
+        } else {
+            Err(StarsigError::InvalidSignature)
+        }
+    }
+
+    /// Encodes the proof as `R || T || z`.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut buf = [0u8; Self::SIZE];
+        buf[..32].copy_from_slice(self.R.as_bytes());
+        buf[32..64].copy_from_slice(self.T.as_bytes());
+        buf[64..].copy_from_slice(self.z.as_bytes());
+        buf
+    }
+
+    /// Decodes the proof from `R || T || z`.
+    /// Fails with `StarsigError::InvalidSignature` if the scalar `z` is not canonical.
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Result<SignatureProof, StarsigError> {
+        let mut Rbuf = [0u8; 32];
+        let mut Tbuf = [0u8; 32];
+        let mut zbuf = [0u8; 32];
+        Rbuf[..].copy_from_slice(&bytes[..32]);
+        Tbuf[..].copy_from_slice(&bytes[32..64]);
+        zbuf[..].copy_from_slice(&bytes[64..]);
+        Ok(SignatureProof {
+            R: CompressedRistretto(Rbuf),
+            T: CompressedRistretto(Tbuf),
+            z: Scalar::from_canonical_bytes(zbuf).ok_or(StarsigError::InvalidSignature)?,
+        })
+    }
+
+    /// Makes the challenge `e` of the proof of knowledge, bound to the signed message
+    /// through the `transcript`, to the key `X`, the nonce commitment `R` and the commitment `T`.
+    fn challenge(
+        transcript: &mut Transcript,
+        X: &VerificationKey,
+        R: &CompressedRistretto,
+        T: &CompressedRistretto,
+    ) -> Scalar {
+        transcript.append_message(b"dom-sep", b"starsig signature proof");
+        transcript.append_point(LABEL_KEY, X.as_point());
+        transcript.append_point(LABEL_NONCE, R);
+        transcript.append_point(b"T", T);
+        transcript.challenge_scalar(b"e")
+    }
+}
@@ -0,0 +1,82 @@
+use curve25519_dalek::constants::{RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_POINT};
+
+use super::errors::StarsigError;
+use super::key::VerificationKey;
+use super::signature::Signature;
+use super::transcript::{ChallengeLayout, TranscriptProtocol};
+
+/// Outcome of `Signature::verify_report`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    /// The signature is valid.
+    Valid,
+    /// The signature is valid, but the verification key is suspicious.
+    ValidWithWarnings(Vec<Warning>),
+    /// The signature is invalid.
+    Invalid(StarsigError),
+}
+
+/// Reason of a failed `Signature::verify_detailed`, naming the first malformed part of the input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VerifyFailure {
+    /// The nonce commitment `R` is not a valid compressed Ristretto point.
+    BadNonce,
+    /// The verification key is not a valid compressed Ristretto point, or is the identity.
+    BadKey,
+    /// Both points are valid, but `s·G != R + c·X`.
+    EquationMismatch,
+}
+
+/// Property of a valid verification key that a cautious verifier may want to reject.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Warning {
+    /// The key is the base point, i.e. its secret key is 1.
+    BasepointKey,
+}
+
+impl Signature {
+    /// Verifies the signature like `verify`, and additionally reports suspicious properties
+    /// of the verification key. Warnings do not affect validity of the signature.
+    pub fn verify_report(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+    ) -> VerifyOutcome {
+        if let Err(err) = self.verify(transcript, pubkey) {
+            return VerifyOutcome::Invalid(err);
+        }
+        let mut warnings = Vec::new();
+        if pubkey.as_point() == &RISTRETTO_BASEPOINT_COMPRESSED {
+            warnings.push(Warning::BasepointKey);
+        }
+        if warnings.is_empty() {
+            VerifyOutcome::Valid
+        } else {
+            VerifyOutcome::ValidWithWarnings(warnings)
+        }
+    }
+
+    /// Verifies the signature like `verify`, and reports which check failed:
+    /// decompression of `R`, decompression of the key, or the verification equation.
+    /// Intended for diagnosing malformed data; the checks are done in that order.
+    pub fn verify_detailed(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+    ) -> Result<(), VerifyFailure> {
+        let R = self.R.decompress().ok_or(VerifyFailure::BadNonce)?;
+        if pubkey == VerificationKey::IDENTITY {
+            return Err(VerifyFailure::BadKey);
+        }
+        let X = pubkey
+            .as_point()
+            .decompress()
+            .ok_or(VerifyFailure::BadKey)?;
+        let c = ChallengeLayout::KeyFirst.challenge(transcript, pubkey.as_point(), &self.R);
+        if RISTRETTO_BASEPOINT_POINT * self.s == R + c * X {
+            Ok(())
+        } else {
+            Err(VerifyFailure::EquationMismatch)
+        }
+    }
+}
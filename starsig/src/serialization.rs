@@ -1,15 +1,26 @@
 use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
+use std::convert::TryFrom;
+
 use serde::{de::Deserializer, de::Visitor, ser::Serializer, Deserialize, Serialize};
 
 use super::Signature;
 use super::StarsigError;
 
 impl Signature {
+    /// Size of the encoded signature in bytes.
+    pub const SIZE: usize = 64;
+
+    /// Checks that the slice has the length of an encoded signature.
+    /// This does not check that the contents are valid.
+    pub fn is_well_formed_len(bytes: &[u8]) -> bool {
+        bytes.len() == Self::SIZE
+    }
+
     /// Decodes a signature from a 64-byte slice.
     pub fn from_bytes(sig: impl AsRefExt) -> Result<Self, StarsigError> {
         let sig = sig.as_ref_ext();
-        if sig.len() != 64 {
+        if !Self::is_well_formed_len(sig) {
             return Err(StarsigError::InvalidSignature);
         }
         let mut Rbuf = [0u8; 32];
@@ -23,14 +34,44 @@ impl Signature {
     }
 
     /// Encodes the signature as a 64-byte array.
-    pub fn to_bytes(&self) -> [u8; 64] {
-        let mut buf = [0u8; 64];
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut buf = [0u8; Self::SIZE];
         buf[..32].copy_from_slice(self.R.as_bytes());
         buf[32..].copy_from_slice(self.s.as_bytes());
         buf
     }
 }
 
+impl From<[u8; Signature::SIZE]> for Signature {
+    /// Converts the 64-byte encoding into a signature without validation:
+    /// the scalar `s` is not checked to be canonical, so the conversion never fails.
+    /// Use `Signature::from_bytes` to reject non-canonical encodings,
+    /// or verify the signature with `Signature::verify_strict`, which checks it.
+    ///
+    /// The conversion is lossy for an `s` with the top bit (bit 255) set: a scalar
+    /// holds only 255 bits, so the bit is cleared and `to_bytes` does not return the input.
+    /// Valid signatures are not affected, since a canonical `s` is below `2^253`.
+    fn from(bytes: [u8; Signature::SIZE]) -> Self {
+        let mut Rbuf = [0u8; 32];
+        let mut sbuf = [0u8; 32];
+        Rbuf[..].copy_from_slice(&bytes[..32]);
+        sbuf[..].copy_from_slice(&bytes[32..]);
+        Signature {
+            R: CompressedRistretto(Rbuf),
+            s: Scalar::from_bits(sbuf),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = StarsigError;
+
+    /// Decodes a signature like `Signature::from_bytes`.
+    fn try_from(bytes: &[u8]) -> Result<Self, StarsigError> {
+        Signature::from_bytes(bytes)
+    }
+}
+
 /// Same as `AsRef<[u8]>`, but extended to 64-byte array.
 pub trait AsRefExt {
     /// Returns a slice
@@ -1,15 +1,19 @@
 use core::iter;
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
-use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
 use std::fmt;
 
 use merlin::Transcript;
 
-use super::batch::{BatchVerification, SingleVerifier};
+use super::batch::{BatchVerification, BatchVerifier, SingleVerifier};
 use super::errors::StarsigError;
 use super::key::VerificationKey;
-use super::transcript::TranscriptProtocol;
+use super::message::{PrehashedMessage, StrictTranscript};
+use super::nonce::DeterministicNonce;
+use super::transcript::{ChallengeLayout, TranscriptProtocol};
+use super::witness::VerifyWitness;
 
 /// A Schnorr signature.
 #[derive(Copy, Clone)]
@@ -23,8 +27,15 @@ pub struct Signature {
 impl Signature {
     /// Creates a signature for a single private key and single message
     pub fn sign(transcript: &mut Transcript, privkey: Scalar) -> Signature {
-        let X = VerificationKey::from_secret(&privkey); // pubkey
+        Self::sign_with_layout(transcript, privkey, ChallengeLayout::default())
+    }
 
+    /// Creates a signature like `sign`, computing the challenge with the given `layout`.
+    pub fn sign_with_layout(
+        transcript: &mut Transcript,
+        privkey: Scalar,
+        layout: ChallengeLayout,
+    ) -> Signature {
         let mut rng = transcript
             .build_rng()
             .rekey_with_witness_bytes(b"x", &privkey.to_bytes())
@@ -32,15 +43,55 @@ impl Signature {
 
         // Generate ephemeral keypair (r, R). r is a random nonce.
         let r = Scalar::random(&mut rng);
+
+        Self::sign_with_r(transcript, privkey, r, layout)
+    }
+
+    /// Creates a signature for a single private key and single message,
+    /// additionally folding caller-provided `extra_entropy` into the nonce derivation
+    /// together with the transcript, the private key and the system RNG ("hedged" signature).
+    /// This guards against side-channel and fault attacks on the derivation,
+    /// and against a weak system RNG.
+    pub fn sign_hedged(
+        transcript: &mut Transcript,
+        privkey: Scalar,
+        extra_entropy: &[u8],
+    ) -> Signature {
+        let mut rng = transcript
+            .build_rng()
+            .rekey_with_witness_bytes(b"x", &privkey.to_bytes())
+            .rekey_with_witness_bytes(b"extra_entropy", extra_entropy)
+            .finalize(&mut rand::thread_rng());
+        let r = Scalar::random(&mut rng);
+
+        Self::sign_with_r(transcript, privkey, r, ChallengeLayout::default())
+    }
+
+    /// Creates a signature for a single private key and single message,
+    /// deriving the nonce deterministically with the given `nonce` source
+    /// instead of the system RNG.
+    pub fn sign_deterministic(
+        transcript: &mut Transcript,
+        privkey: Scalar,
+        nonce: &DeterministicNonce,
+    ) -> Signature {
+        let r = nonce.derive(transcript, &privkey);
+
+        Self::sign_with_r(transcript, privkey, r, ChallengeLayout::default())
+    }
+
+    fn sign_with_r(
+        transcript: &mut Transcript,
+        privkey: Scalar,
+        r: Scalar,
+        layout: ChallengeLayout,
+    ) -> Signature {
+        let X = VerificationKey::from_secret(&privkey); // pubkey
+
         // R = generator * r
         let R = (RISTRETTO_BASEPOINT_POINT * r).compress();
 
-        let c = {
-            transcript.starsig_domain_sep();
-            transcript.append_point(b"X", X.as_point());
-            transcript.append_point(b"R", &R);
-            transcript.challenge_scalar(b"c")
-        };
+        let c = layout.challenge(transcript, X.as_point(), &R);
 
         let s = r + c * privkey;
 
@@ -50,12 +101,226 @@ impl Signature {
     /// Verifies the signature over a transcript using the provided verification key.
     /// Transcript should be in the same state as it was during the `sign` call
     /// that created the signature.
+    /// The message must already be committed to the transcript
+    /// (see also `verify_on_committed_transcript` and `verify_committing_message`).
+    /// Nothing is assumed about the other contents of the transcript, which may include
+    /// any prior protocol messages, e.g. a handshake: the key `X` and the nonce commitment `R`
+    /// are committed on top of the given state, so the signature covers everything committed before.
+    /// Verification never panics on malformed signatures: invalid points fail decompression
+    /// and arithmetic on scalars is total, so any input results in `Ok` or `Err`.
+    /// Verification against `VerificationKey::IDENTITY` fails with `StarsigError::InvalidKey`.
+    ///
+    /// The transcript is advanced in place, without an internal copy: after the call it is
+    /// in the same state as the signer's transcript after `sign`. Callers that need
+    /// the original state must clone the transcript themselves before verification.
+    ///
+    /// With the `tracing` feature, the check of the verification equation is done
+    /// within a `starsig.verify` span carrying the key and the nonce commitment.
     pub fn verify(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "starsig.verify",
+            key = %hex::encode(pubkey.as_bytes()),
+            nonce = %hex::encode(self.R.as_bytes())
+        )
+        .entered();
+
+        let result = if pubkey == VerificationKey::IDENTITY {
+            Err(StarsigError::InvalidKey)
+        } else {
+            SingleVerifier::verify(|verifier| self.verify_batched(transcript, pubkey, verifier))
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!("verified: {}", result.is_ok());
+        result
+    }
+
+    /// Computes the difference `D = s·G - R - c·X` of the two sides of the verification equation,
+    /// which is the identity point if and only if the signature is valid.
+    /// Intended for inspecting failing signatures while debugging; use `verify` to verify signatures.
+    /// Fails if `R` or the key are not valid points.
+    #[cfg(feature = "debug-tools")]
+    pub fn verify_difference(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+    ) -> Result<CompressedRistretto, StarsigError> {
+        let c = self.challenge(transcript, pubkey.as_point());
+        let R = self.R.decompress().ok_or(StarsigError::InvalidSignature)?;
+        let X = pubkey
+            .into_point()
+            .decompress()
+            .ok_or(StarsigError::InvalidKey)?;
+        Ok((RISTRETTO_BASEPOINT_POINT * self.s - R - X * c).compress())
+    }
+
+    /// Verifies a signature whose nonce commitment `R` and scalar `s` were transmitted separately,
+    /// e.g. `R` in a message header and `s` in the body.
+    /// Equivalent to `verify` on the signature `{s, R}`.
+    pub fn verify_split(
+        R: CompressedRistretto,
+        s: Scalar,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        Signature { s, R }.verify(transcript, pubkey)
+    }
+
+    /// Verifies the signature like `verify`, after checking that its nonce commitment `R`
+    /// is the sum of the `nonces`, e.g. the per-party nonce commitments of a multi-party signature.
+    /// This ties the signature to the specific nonces used by the parties.
+    /// Fails with `StarsigError::InvalidSignature` if a nonce is not a valid point or the sum does not match.
+    pub fn verify_with_nonces(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+        nonces: &[CompressedRistretto],
+    ) -> Result<(), StarsigError> {
+        let sum: RistrettoPoint = nonces
+            .iter()
+            .map(|nonce| nonce.decompress().ok_or(StarsigError::InvalidSignature))
+            .sum::<Result<_, _>>()?;
+        if sum.compress() != self.R {
+            return Err(StarsigError::InvalidSignature);
+        }
+        self.verify(transcript, pubkey)
+    }
+
+    /// Verifies the signature, and on success fills `out` with the challenge bytes
+    /// under `label` from the advanced transcript, e.g. to derive a session key bound to the signature.
+    /// The transcript commits the key and the nonce `R` during verification,
+    /// and the scalar `s` is committed under the label "s" before the bytes are produced.
+    /// On failure `out` is left unchanged.
+    pub fn verify_then_challenge_bytes(
         &self,
         transcript: &mut Transcript,
         pubkey: VerificationKey,
+        label: &'static [u8],
+        out: &mut [u8],
+    ) -> Result<(), StarsigError> {
+        self.verify(transcript, pubkey)?;
+        transcript.append_scalar(b"s", &self.s);
+        transcript.challenge_bytes(label, out);
+        Ok(())
+    }
+
+    /// Verifies the signature over a transcript to which the message has already been committed.
+    /// This is the same as `verify`, named explicitly to distinguish it from `verify_committing_message`.
+    pub fn verify_on_committed_transcript(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        self.verify(transcript, pubkey)
+    }
+
+    /// Verifies the signature over a `message` that is not yet committed to the transcript:
+    /// the message is committed under the `label` to the base `transcript`, which is then verified.
+    /// This matches a signer that committed the message to the same base transcript before `sign`.
+    pub fn verify_committing_message(
+        &self,
+        transcript: &mut Transcript,
+        label: &'static [u8],
+        message: &[u8],
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        transcript.commit_field(label, message);
+        self.verify(transcript, pubkey)
+    }
+
+    /// Verifies the signature like `verify`, and returns the challenge and the points
+    /// of the verification equation as a `VerifyWitness`.
+    pub fn verify_with_witness(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+    ) -> Result<VerifyWitness, StarsigError> {
+        let c = self.challenge(transcript, pubkey.as_point());
+        self.verify_precomputed(c, pubkey)?;
+        Ok(VerifyWitness {
+            c,
+            s: self.s,
+            R: self.R,
+            X: pubkey.into_point(),
+        })
+    }
+
+    /// Verifies the signature like `verify`, computing the challenge with the given `layout`.
+    pub fn verify_with_layout(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+        layout: ChallengeLayout,
+    ) -> Result<(), StarsigError> {
+        let c = layout.challenge(transcript, pubkey.as_point(), &self.R);
+        self.verify_precomputed(c, pubkey)
+    }
+
+    /// Verifies the signature like `verify`, but additionally rejects degenerate signatures
+    /// with `s == 0`. For these the verification equation reduces to `R == -c·X`,
+    /// which can be satisfied without the knowledge of the secret key
+    /// (e.g. with identity key and nonce).
+    /// Signatures with a non-canonical `s` (e.g. converted from bytes with `From<[u8; 64]>`)
+    /// are rejected too.
+    /// The key is also checked with `VerificationKey::is_torsion_free`,
+    /// failing with `StarsigError::InvalidKey` otherwise.
+    pub fn verify_strict(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
     ) -> Result<(), StarsigError> {
-        SingleVerifier::verify(|verifier| self.verify_batched(transcript, pubkey, verifier))
+        if self.s == Scalar::zero() || !self.s.is_canonical() {
+            return Err(StarsigError::InvalidSignature);
+        }
+        if !pubkey.is_torsion_free() {
+            return Err(StarsigError::InvalidKey);
+        }
+        self.verify(transcript, pubkey)
+    }
+
+    /// Verifies the signature over a transcript passed by value, and returns the transcript
+    /// advanced past the verification, so that it can be used in the next stage of the protocol.
+    /// Transcript should be in the same state as it was during the `sign` call
+    /// that created the signature.
+    pub fn verify_owned(
+        &self,
+        mut transcript: Transcript,
+        pubkey: VerificationKey,
+    ) -> Result<Transcript, StarsigError> {
+        self.verify(&mut transcript, pubkey)?;
+        Ok(transcript)
+    }
+
+    /// Creates a signature over a sub-statement of a larger protocol.
+    /// The transcript is forked with the given `label`, so that the original transcript
+    /// remains unaffected and can be used to continue the protocol.
+    pub fn sign_forked(
+        transcript: &Transcript,
+        label: &'static [u8],
+        privkey: Scalar,
+    ) -> Signature {
+        Self::sign(&mut Self::fork_transcript(transcript, label), privkey)
+    }
+
+    /// Verifies the signature created with `sign_forked` over a fork of the transcript.
+    /// The original transcript remains unaffected and can be used to continue the protocol.
+    pub fn verify_forked(
+        &self,
+        transcript: &Transcript,
+        label: &'static [u8],
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        self.verify(&mut Self::fork_transcript(transcript, label), pubkey)
+    }
+
+    fn fork_transcript(transcript: &Transcript, label: &'static [u8]) -> Transcript {
+        let mut fork = transcript.clone();
+        fork.append_message(b"fork", label);
+        fork
     }
 
     /// Verifies the signature against a given verification key in a batch.
@@ -63,17 +328,140 @@ impl Signature {
     /// that created the signature.
     pub fn verify_batched(
         &self,
-        transcript: &mut Transcript,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+        batch: &mut impl BatchVerification,
+    ) {
+        let c = self.challenge(transcript, pubkey.as_point());
+        self.verify_precomputed_batched(c, pubkey, batch);
+    }
+
+    /// Verifies the signature against a given verification key and a challenge `c`
+    /// that was already computed from the transcript.
+    pub fn verify_precomputed(
+        &self,
+        c: Scalar,
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        if pubkey == VerificationKey::IDENTITY {
+            return Err(StarsigError::InvalidKey);
+        }
+        SingleVerifier::verify(|verifier| self.verify_precomputed_batched(c, pubkey, verifier))
+    }
+
+    /// Verifies a list of `(c, X, R, s)` tuples with precomputed challenges `c`
+    /// in a single batch with random weights, without rebuilding the transcripts.
+    pub fn verify_precomputed_batch(
+        tuples: &[(Scalar, VerificationKey, CompressedRistretto, Scalar)],
+    ) -> Result<(), StarsigError> {
+        let mut batch = BatchVerifier::with_capacity(rand::thread_rng(), tuples.len());
+        for &(c, pubkey, R, s) in tuples {
+            Signature { s, R }.verify_precomputed_batched(c, pubkey, &mut batch);
+        }
+        batch.verify()
+    }
+
+    /// Verifies a list of `(c, X, R, s)` tuples like `verify_precomputed_batch`,
+    /// but returns the validity of each tuple. If the batch verification fails,
+    /// the tuples are checked individually to find the invalid ones.
+    pub fn verify_batch_mask(
+        tuples: &[(Scalar, VerificationKey, CompressedRistretto, Scalar)],
+    ) -> Vec<bool> {
+        if Self::verify_precomputed_batch(tuples).is_ok() {
+            return vec![true; tuples.len()];
+        }
+        tuples
+            .iter()
+            .map(|&(c, pubkey, R, s)| Signature { s, R }.verify_precomputed(c, pubkey).is_ok())
+            .collect()
+    }
+
+    /// Verifies a stream of `(transcript, key, signature)` items in a single batch,
+    /// folding the terms of each item into the batch as the iterator is consumed,
+    /// so the items need not be collected first (e.g. when reading them from the network).
+    /// An empty stream verifies successfully, like an empty `BatchVerifier`.
+    pub fn verify_batch_iter<I>(items: I) -> Result<(), StarsigError>
+    where
+        I: IntoIterator<Item = (Transcript, VerificationKey, Signature)>,
+    {
+        let items = items.into_iter();
+        let mut batch = BatchVerifier::with_capacity(rand::thread_rng(), items.size_hint().0);
+        for (mut transcript, pubkey, signature) in items {
+            signature.verify_batched(&mut transcript, pubkey, &mut batch);
+        }
+        batch.verify()
+    }
+
+    /// Verifies a stream of `(transcript, key, signature)` items one by one, in order,
+    /// stopping at the first invalid signature. Unlike `verify_batch_iter`, the failure
+    /// identifies the item: it is returned with its index, and the later items are not consumed.
+    pub fn verify_sequential<I>(items: I) -> Result<(), (usize, StarsigError)>
+    where
+        I: IntoIterator<Item = (Transcript, VerificationKey, Signature)>,
+    {
+        for (i, (mut transcript, pubkey, signature)) in items.into_iter().enumerate() {
+            signature
+                .verify(&mut transcript, pubkey)
+                .map_err(|err| (i, err))?;
+        }
+        Ok(())
+    }
+
+    /// Verifies signatures by different keys over the same message that share
+    /// a single nonce commitment `R`, as found in some legacy batch formats.
+    /// The `items` are pairs of a key `X_i` and its scalar `s_i`, and each challenge `c_i`
+    /// is computed over a clone of the `transcript` with `X_i` and `R`.
+    /// The batch is valid if `(sum_i s_i) * G == n * R + sum_i( c_i * X_i )`.
+    /// An empty batch fails with `StarsigError::InvalidBatch`.
+    ///
+    /// **Security warning:** do not produce signatures with shared nonces.
+    /// Anyone who sees two signatures by the same key with the same nonce over
+    /// different messages can recover the private key. Also, the check only covers
+    /// the sum of the `s_i`: the individual signatures are not verified,
+    /// so the scalars can be redistributed among the keys without failing the check.
+    /// This function exists only to validate the legacy formats for interoperability.
+    pub fn verify_shared_nonce(
+        R: CompressedRistretto,
+        items: &[(VerificationKey, Scalar)],
+        transcript: &Transcript,
+    ) -> Result<(), StarsigError> {
+        if items.is_empty() {
+            return Err(StarsigError::InvalidBatch);
+        }
+        if items.iter().any(|(X, _)| *X == VerificationKey::IDENTITY) {
+            return Err(StarsigError::InvalidKey);
+        }
+        let sig = Signature {
+            s: items.iter().map(|(_, s)| s).sum(),
+            R,
+        };
+        let challenges: Vec<Scalar> = items
+            .iter()
+            .map(|(X, _)| sig.challenge(&mut transcript.clone(), X.as_point()))
+            .collect();
+
+        // `0 == (-sum_i s_i) * G + n * R + sum_i( c_i * X_i )`
+        SingleVerifier::verify(|verifier| {
+            verifier.append(
+                -sig.s,
+                iter::once(Scalar::from(items.len() as u64)).chain(challenges),
+                iter::once(R.decompress())
+                    .chain(items.iter().map(|(X, _)| X.into_point().decompress())),
+            )
+        })
+    }
+
+    fn verify_precomputed_batched(
+        &self,
+        c: Scalar,
         pubkey: VerificationKey,
         batch: &mut impl BatchVerification,
     ) {
-        // Make c = H(pubkey, R, m)
-        // The message has already been fed into the transcript
-        let c = {
-            transcript.starsig_domain_sep();
-            transcript.append_point(b"X", pubkey.as_point());
-            transcript.append_point(b"R", &self.R);
-            transcript.challenge_scalar(b"c")
+        // Identity key is treated as an invalid point, so that the batch fails.
+        let pubkey_point = if pubkey == VerificationKey::IDENTITY {
+            None
+        } else {
+            pubkey.into_point().decompress()
         };
 
         // Form the final linear combination:
@@ -83,9 +471,99 @@ impl Signature {
         batch.append(
             -self.s,
             iter::once(Scalar::one()).chain(iter::once(c)),
-            iter::once(self.R.decompress()).chain(iter::once(pubkey.into_point().decompress())),
+            iter::once(self.R.decompress()).chain(iter::once(pubkey_point)),
         );
     }
+
+    /// Verifies the signature against an already decompressed verification key.
+    /// This skips decompression of the key, which is useful when keys are kept decompressed.
+    /// Transcript should be in the same state as it was during the `sign` call
+    /// that created the signature.
+    pub fn verify_point(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: RistrettoPoint,
+    ) -> Result<(), StarsigError> {
+        if pubkey.is_identity() {
+            return Err(StarsigError::InvalidKey);
+        }
+        SingleVerifier::verify(|verifier| {
+            let c = self.challenge(transcript, &pubkey.compress());
+            verifier.append(
+                -self.s,
+                iter::once(Scalar::one()).chain(iter::once(c)),
+                iter::once(self.R.decompress()).chain(iter::once(Some(pubkey))),
+            );
+        })
+    }
+
+    /// Returns the nonce commitment `R` as a verification key, for protocols that operate
+    /// on it as a point (e.g. adaptor signatures). The point is not checked to be valid.
+    pub fn nonce_as_key(&self) -> VerificationKey {
+        VerificationKey::from_compressed(self.R)
+    }
+
+    /// Creates a signature like `sign`, with the generator `G` in place of the Ristretto base point,
+    /// for protocols with a custom generator (e.g. the second Pedersen generator).
+    /// The verification key for the signature is `privkey * G`.
+    pub fn sign_with_generator(
+        transcript: &mut Transcript,
+        privkey: Scalar,
+        G: RistrettoPoint,
+    ) -> Signature {
+        let mut rng = transcript
+            .build_rng()
+            .rekey_with_witness_bytes(b"x", &privkey.to_bytes())
+            .finalize(&mut rand::thread_rng());
+
+        // Generate ephemeral keypair (r, R) with respect to `G`.
+        let r = Scalar::random(&mut rng);
+        let R = (r * G).compress();
+        let X = (privkey * G).compress();
+
+        // Make c = H(X, R, m)
+        let c = ChallengeLayout::KeyFirst.challenge(transcript, &X, &R);
+
+        Signature {
+            s: r + c * privkey,
+            R,
+        }
+    }
+
+    /// Verifies the signature made with `sign_with_generator`: `s·G == R + c·X`
+    /// for the generator `G` in place of the Ristretto base point.
+    /// Transcript should be in the same state as it was during the `sign_with_generator` call.
+    pub fn verify_with_generator(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: VerificationKey,
+        G: RistrettoPoint,
+    ) -> Result<(), StarsigError> {
+        if pubkey == VerificationKey::IDENTITY {
+            return Err(StarsigError::InvalidKey);
+        }
+        let R = self.R.decompress().ok_or(StarsigError::InvalidSignature)?;
+        let X = pubkey
+            .as_point()
+            .decompress()
+            .ok_or(StarsigError::InvalidKey)?;
+        let c = self.challenge(transcript, pubkey.as_point());
+        if self.s * G == R + c * X {
+            Ok(())
+        } else {
+            Err(StarsigError::InvalidSignature)
+        }
+    }
+
+    /// Makes c = H(pubkey, R, m).
+    /// The message has already been fed into the transcript.
+    fn challenge(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        pubkey: &CompressedRistretto,
+    ) -> Scalar {
+        ChallengeLayout::KeyFirst.challenge(transcript, pubkey, &self.R)
+    }
 }
 
 // Message-oriented API
@@ -127,9 +605,334 @@ impl Signature {
         )
     }
 
+    /// Verifies signatures packed back-to-back in a `blob` against the corresponding
+    /// `keys` and `messages` signed with `sign_message` under the same `label`.
+    /// Signatures are decoded in place and verified in a single batch.
+    /// Fails if the blob length is not a multiple of the signature size,
+    /// or if the number of signatures, keys and messages do not match.
+    pub fn verify_packed(
+        label: &'static [u8],
+        blob: &[u8],
+        keys: &[VerificationKey],
+        messages: &[&[u8]],
+    ) -> Result<(), StarsigError> {
+        let n = blob.len() / Self::SIZE;
+        if n * Self::SIZE != blob.len() || keys.len() != n || messages.len() != n {
+            return Err(StarsigError::InvalidSignature);
+        }
+        let mut batch = BatchVerifier::with_capacity(rand::thread_rng(), n);
+        for ((chunk, pubkey), message) in blob.chunks_exact(Self::SIZE).zip(keys).zip(messages) {
+            Self::from_bytes(chunk)?.verify_message_batched(label, message, *pubkey, &mut batch);
+        }
+        batch.verify()
+    }
+
+    /// Signs a prehashed message with a given domain-separation label.
+    /// Internally it creates a Transcript instance labelled "Starsig.sign_prehashed",
+    /// and appends to it the prehash labelled with a user-provided `label`.
+    pub fn sign_prehashed(
+        label: &'static [u8],
+        prehash: &PrehashedMessage,
+        privkey: Scalar,
+    ) -> Signature {
+        Self::sign(&mut Self::transcript_for_prehash(label, prehash), privkey)
+    }
+
+    /// Verifies the signature over a prehashed message using the provided verification key.
+    /// Internally it creates a Transcript instance labelled "Starsig.sign_prehashed",
+    /// and appends to it the prehash labelled with a user-provided `label`.
+    pub fn verify_prehashed(
+        &self,
+        label: &'static [u8],
+        prehash: &PrehashedMessage,
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        self.verify(&mut Self::transcript_for_prehash(label, prehash), pubkey)
+    }
+
+    /// Signs a message in a transcript labeled with the `domain`,
+    /// with the message committed under the label "message".
+    /// Fails with `StarsigError::EmptyDomain` if the `domain` is empty, like `verify_simple`.
+    pub fn sign_simple(
+        domain: &'static [u8],
+        message: &[u8],
+        privkey: Scalar,
+    ) -> Result<Signature, StarsigError> {
+        Self::check_domain(domain)?;
+        Ok(Self::sign(
+            &mut Self::signing_transcript(domain, message),
+            privkey,
+        ))
+    }
+
+    /// Verifies the signature over a message without any transcript handling by the caller.
+    /// Creates a fresh transcript labeled with the `domain`, commits the message
+    /// under the label "message", and verifies the signature.
+    /// Fails with `StarsigError::EmptyDomain` if the `domain` is empty.
+    pub fn verify_simple(
+        &self,
+        message: &[u8],
+        domain: &'static [u8],
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        Self::check_domain(domain)?;
+        self.verify(&mut Self::signing_transcript(domain, message), pubkey)
+    }
+
+    /// Verifies signatures by several keys over the same message made with `sign_simple`,
+    /// e.g. committee attestations. The message is committed to the transcript once,
+    /// and the transcript is cloned for each item. Returns the validity of each signature.
+    /// Fails with `StarsigError::EmptyDomain` if the `domain` is empty.
+    pub fn verify_shared_message(
+        message: &[u8],
+        domain: &'static [u8],
+        items: &[(VerificationKey, Signature)],
+    ) -> Result<Vec<bool>, StarsigError> {
+        Self::check_domain(domain)?;
+        let transcript = Self::signing_transcript(domain, message);
+        Ok(items
+            .iter()
+            .map(|(pubkey, sig)| sig.verify(&mut transcript.clone(), *pubkey).is_ok())
+            .collect())
+    }
+
+    /// Verifies a chain of signatures, e.g. an audit log, where the first entry signs
+    /// the `genesis_message` and every next entry signs the encoding of the previous signature,
+    /// each with `sign_simple` under the `domain`.
+    /// Fails at the first invalid link, returning its index, like `verify_sequential`.
+    pub fn verify_chain(
+        entries: &[(VerificationKey, Signature)],
+        genesis_message: &[u8],
+        domain: &'static [u8],
+    ) -> Result<(), (usize, StarsigError)> {
+        Self::check_domain(domain).map_err(|err| (0, err))?;
+        let mut message = genesis_message.to_vec();
+        for (i, (pubkey, sig)) in entries.iter().enumerate() {
+            sig.verify_simple(&message, domain, *pubkey)
+                .map_err(|err| (i, err))?;
+            message = sig.to_bytes().to_vec();
+        }
+        Ok(())
+    }
+
+    /// Signs a message like `sign_simple`, under a `domain` constructed at runtime (e.g. per chain).
+    /// Since transcript labels are static, the transcript is labeled "Starsig.dynamic-domain",
+    /// and the domain and the message are committed as fields under the labels "domain" and "message".
+    /// Fails with `StarsigError::EmptyDomain` if the `domain` is empty.
+    pub fn sign_simple_dynamic(
+        domain: &[u8],
+        message: &[u8],
+        privkey: Scalar,
+    ) -> Result<Signature, StarsigError> {
+        Self::check_domain(domain)?;
+        Ok(Self::sign(
+            &mut Self::dynamic_signing_transcript(domain, message),
+            privkey,
+        ))
+    }
+
+    /// Verifies the signature over a message under a `domain` constructed at runtime,
+    /// made with `sign_simple_dynamic`.
+    /// Fails with `StarsigError::EmptyDomain` if the `domain` is empty.
+    pub fn verify_simple_dynamic(
+        &self,
+        message: &[u8],
+        domain: &[u8],
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        Self::check_domain(domain)?;
+        self.verify(
+            &mut Self::dynamic_signing_transcript(domain, message),
+            pubkey,
+        )
+    }
+
+    /// Signs the `endorsed` key, e.g. to delegate authority to it.
+    /// The key is committed to the transcript under the dedicated label "endorsed-key",
+    /// so an endorsement cannot be confused with a signature over data.
+    pub fn sign_endorsement(
+        transcript: &mut Transcript,
+        privkey: Scalar,
+        endorsed: VerificationKey,
+    ) -> Signature {
+        transcript.append_point(b"endorsed-key", endorsed.as_point());
+        Self::sign(transcript, privkey)
+    }
+
+    /// Verifies the endorsement of the `endorsed` key by the `signer` key.
+    /// Transcript should be in the same state as it was during the `sign_endorsement` call.
+    pub fn verify_endorsement(
+        &self,
+        transcript: &mut impl TranscriptProtocol,
+        signer: VerificationKey,
+        endorsed: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        transcript.append_point(b"endorsed-key", endorsed.as_point());
+        self.verify(transcript, signer)
+    }
+
+    /// Signs a message like `sign_simple`, but commits the `epoch` under the label "epoch"
+    /// before the message, so the signature cannot be replayed in another epoch.
+    /// Fails with `StarsigError::EmptyDomain` if the `domain` is empty.
+    pub fn sign_with_epoch(
+        domain: &'static [u8],
+        epoch: u64,
+        message: &[u8],
+        privkey: Scalar,
+    ) -> Result<Signature, StarsigError> {
+        Self::check_domain(domain)?;
+        Ok(Self::sign(
+            &mut Self::transcript_for_epoch(domain, epoch, message),
+            privkey,
+        ))
+    }
+
+    /// Verifies the signature made with `sign_with_epoch` in the same `epoch`.
+    /// Fails with `StarsigError::EmptyDomain` if the `domain` is empty.
+    pub fn verify_with_epoch(
+        &self,
+        domain: &'static [u8],
+        epoch: u64,
+        message: &[u8],
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        Self::check_domain(domain)?;
+        self.verify(
+            &mut Self::transcript_for_epoch(domain, epoch, message),
+            pubkey,
+        )
+    }
+
+    /// Signs a message like `sign_simple`, but commits the `role` of the signer
+    /// (e.g. "proposer" or "attester") under the label "role" before the message,
+    /// so that both the nonce and the challenge are bound to the role,
+    /// and a signature made in one role does not verify in another.
+    /// Fails with `StarsigError::EmptyDomain` if the `domain` is empty.
+    pub fn sign_with_role(
+        domain: &'static [u8],
+        role: &[u8],
+        message: &[u8],
+        privkey: Scalar,
+    ) -> Result<Signature, StarsigError> {
+        Self::check_domain(domain)?;
+        Ok(Self::sign(
+            &mut Self::transcript_for_role(domain, role, message),
+            privkey,
+        ))
+    }
+
+    /// Verifies the signature made with `sign_with_role` in the same `role`.
+    /// Fails with `StarsigError::EmptyDomain` if the `domain` is empty.
+    pub fn verify_with_role(
+        &self,
+        domain: &'static [u8],
+        role: &[u8],
+        message: &[u8],
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        Self::check_domain(domain)?;
+        self.verify(
+            &mut Self::transcript_for_role(domain, role, message),
+            pubkey,
+        )
+    }
+
+    /// Returns the transcript used by `sign_simple` and `verify_simple`:
+    /// a transcript labeled with the `domain`, with the message committed under the label "message".
+    /// This allows other implementations and golden tests to reproduce the signatures exactly.
+    pub fn signing_transcript(domain: &'static [u8], message: &[u8]) -> Transcript {
+        let mut t = Transcript::new(domain);
+        t.commit_field(b"message", message);
+        t
+    }
+
+    fn check_domain(domain: &[u8]) -> Result<(), StarsigError> {
+        if domain.is_empty() {
+            Err(StarsigError::EmptyDomain)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn dynamic_signing_transcript(domain: &[u8], message: &[u8]) -> Transcript {
+        let mut t = Transcript::new(b"Starsig.dynamic-domain");
+        t.commit_field(b"domain", domain);
+        t.commit_field(b"message", message);
+        t
+    }
+
+    fn transcript_for_epoch(domain: &'static [u8], epoch: u64, message: &[u8]) -> Transcript {
+        let mut t = Transcript::new(domain);
+        t.append_u64(b"epoch", epoch);
+        t.commit_field(b"message", message);
+        t
+    }
+
+    fn transcript_for_role(domain: &'static [u8], role: &[u8], message: &[u8]) -> Transcript {
+        let mut t = Transcript::new(domain);
+        t.commit_field(b"role", role);
+        t.commit_field(b"message", message);
+        t
+    }
+
+    /// Signs a message committed under a caller-chosen `label` into a clone of the `base` transcript.
+    /// The `base` transcript remains unaffected.
+    pub fn sign_with_message_label(
+        base: &Transcript,
+        label: &'static [u8],
+        message: &[u8],
+        privkey: Scalar,
+    ) -> Signature {
+        Self::sign(&mut Self::labeled_transcript(base, label, message), privkey)
+    }
+
+    /// Verifies the signature over a message committed under a caller-chosen `label`
+    /// into a clone of the `base` transcript.
+    /// The signature created with a different label, or over a different message, fails to verify.
+    pub fn verify_with_message_label(
+        &self,
+        base: &Transcript,
+        label: &'static [u8],
+        message: &[u8],
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        self.verify(&mut Self::labeled_transcript(base, label, message), pubkey)
+    }
+
+    fn labeled_transcript(base: &Transcript, label: &'static [u8], message: &[u8]) -> Transcript {
+        let mut t = base.clone();
+        t.commit_field(label, message);
+        t
+    }
+
+    /// Signs a strict transcript. Fails with `StarsigError::MessageNotCommitted`
+    /// if no message was committed to the transcript.
+    pub fn sign_strict_transcript(
+        transcript: &mut StrictTranscript,
+        privkey: Scalar,
+    ) -> Result<Signature, StarsigError> {
+        Ok(Self::sign(transcript.committed()?, privkey))
+    }
+
+    /// Verifies the signature over a strict transcript. Fails with `StarsigError::MessageNotCommitted`
+    /// if no message was committed to the transcript.
+    pub fn verify_strict_transcript(
+        &self,
+        transcript: &mut StrictTranscript,
+        pubkey: VerificationKey,
+    ) -> Result<(), StarsigError> {
+        self.verify(transcript.committed()?, pubkey)
+    }
+
     fn transcript_for_message(label: &'static [u8], message: &[u8]) -> Transcript {
         let mut t = Transcript::new(b"Starsig.sign_message");
-        t.append_message(label, message);
+        t.commit_field(label, message);
+        t
+    }
+
+    fn transcript_for_prehash(label: &'static [u8], prehash: &PrehashedMessage) -> Transcript {
+        let mut t = Transcript::new(b"Starsig.sign_prehashed");
+        t.commit_field(label, &prehash.0);
         t
     }
 }
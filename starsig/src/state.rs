@@ -0,0 +1,104 @@
+use merlin::Transcript;
+
+use super::errors::StarsigError;
+use super::transcript::TranscriptProtocol;
+
+/// Serializable record of the inputs committed to a transcript, for verifiers
+/// that receive the pre-challenge state together with the signature.
+///
+/// Merlin transcripts cannot be serialized, so the state records the domain and the fields,
+/// and `to_transcript` replays them into a fresh transcript. Since a restored label is not `'static`,
+/// the transcript is labeled "Starsig.transcript-state", the domain is committed under the label "domain",
+/// and each field is committed as its label under "label" followed by its value under "value".
+/// Signatures must therefore be made over `to_transcript` too: they do not verify
+/// against a transcript built directly with `Transcript::new(domain)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TranscriptState {
+    domain: Vec<u8>,
+    fields: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl TranscriptState {
+    /// Creates a state with the given `domain` and no fields.
+    pub fn new(domain: &[u8]) -> Self {
+        TranscriptState {
+            domain: domain.to_vec(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Records a field `value` under the given `label`.
+    pub fn commit_field(&mut self, label: &[u8], value: &[u8]) {
+        self.fields.push((label.to_vec(), value.to_vec()));
+    }
+
+    /// Replays the recorded inputs into a fresh transcript,
+    /// ready to be used with `Signature::sign` and `Signature::verify`.
+    pub fn to_transcript(&self) -> Transcript {
+        let mut t = Transcript::new(b"Starsig.transcript-state");
+        t.commit_field(b"domain", &self.domain);
+        for (label, value) in &self.fields {
+            t.commit_field(b"label", label);
+            t.commit_field(b"value", value);
+        }
+        t
+    }
+
+    /// Encodes the state as the LE32-length-prefixed domain, the LE32 number of fields,
+    /// and the LE32-length-prefixed label and value of each field.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_bytes(&mut buf, &self.domain);
+        buf.extend_from_slice(&(self.fields.len() as u32).to_le_bytes());
+        for (label, value) in &self.fields {
+            write_bytes(&mut buf, label);
+            write_bytes(&mut buf, value);
+        }
+        buf
+    }
+
+    /// Decodes the state encoded with `to_bytes`.
+    /// Fails with `StarsigError::InvalidTranscriptState` if the encoding is truncated
+    /// or followed by extra bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StarsigError> {
+        let mut bytes = bytes;
+        let domain = read_bytes(&mut bytes)?.to_vec();
+        let n = read_u32(&mut bytes)?;
+        let mut fields = Vec::new();
+        for _ in 0..n {
+            let label = read_bytes(&mut bytes)?.to_vec();
+            let value = read_bytes(&mut bytes)?.to_vec();
+            fields.push((label, value));
+        }
+        if !bytes.is_empty() {
+            return Err(StarsigError::InvalidTranscriptState);
+        }
+        Ok(TranscriptState { domain, fields })
+    }
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<u32, StarsigError> {
+    if bytes.len() < 4 {
+        return Err(StarsigError::InvalidTranscriptState);
+    }
+    let (head, tail) = bytes.split_at(4);
+    *bytes = tail;
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(head);
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_bytes<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], StarsigError> {
+    let len = read_u32(bytes)? as usize;
+    if bytes.len() < len {
+        return Err(StarsigError::InvalidTranscriptState);
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
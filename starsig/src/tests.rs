@@ -1,6 +1,16 @@
-use super::{BatchVerifier, Signature, StarsigError, VerificationKey};
+use super::dleq::{self, DleqProof};
+use super::{
+    verify_id, BatchVerifier, ChallengeLayout, DeterministicNonce, Group, GroupSignature, IdProver,
+    MessageBuilder, NonceLedger, PrehashedMessage, PrivateKey, Ristretto, Signature,
+    SignatureProof, StarsigError, StrictTranscript, TranscriptState, VerificationKey,
+    VerifyFailure, VerifyOutcome, Warning, DOMAIN_SEP, LABEL_CHALLENGE, LABEL_DOMAIN_SEP,
+    LABEL_KEY, LABEL_NONCE,
+};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
+use std::convert::TryFrom;
 
 #[test]
 fn sign_and_verify_single() {
@@ -23,6 +33,1720 @@ fn sign_and_verify_single() {
         .is_err());
 }
 
+#[test]
+fn verify_decompressed_key() {
+    let privkey = Scalar::from(1u64);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+
+    let X = VerificationKey::from_secret(&privkey);
+    let X_point = VerificationKey::from_secret_decompressed(&privkey);
+
+    assert_eq!(
+        sig.verify(&mut Transcript::new(b"example transcript"), X),
+        sig.verify_point(&mut Transcript::new(b"example transcript"), X_point)
+    );
+    assert!(sig
+        .verify_point(&mut Transcript::new(b"example transcript"), X_point)
+        .is_ok());
+
+    let X_bad = VerificationKey::from_secret_decompressed(&Scalar::from(2u64));
+    assert!(sig
+        .verify_point(&mut Transcript::new(b"example transcript"), X_bad)
+        .is_err());
+
+    // Transcript states must match the ones after the ordinary verification.
+    let mut t1 = Transcript::new(b"example transcript");
+    let mut t2 = Transcript::new(b"example transcript");
+    sig.verify(&mut t1, X).unwrap();
+    sig.verify_point(&mut t2, X_point).unwrap();
+    let mut c1 = [0u8; 32];
+    let mut c2 = [0u8; 32];
+    t1.challenge_bytes(b"test", &mut c1);
+    t2.challenge_bytes(b"test", &mut c2);
+    assert_eq!(c1, c2);
+}
+
+#[test]
+fn encoding_sizes() {
+    let privkey = Scalar::from(1u64);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let X = VerificationKey::from_secret(&privkey);
+
+    assert_eq!(sig.to_bytes().len(), Signature::SIZE);
+    assert_eq!(X.to_bytes().len(), VerificationKey::SIZE);
+
+    assert!(Signature::is_well_formed_len(&sig.to_bytes()[..]));
+    assert!(!Signature::is_well_formed_len(&sig.to_bytes()[1..]));
+    assert!(!Signature::is_well_formed_len(&[0u8; 65][..]));
+}
+
+#[test]
+fn basepoint_encoding() {
+    use curve25519_dalek::constants::{RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_POINT};
+
+    assert_eq!(
+        hex::encode(RISTRETTO_BASEPOINT_POINT.compress().as_bytes()),
+        "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76"
+    );
+    assert_eq!(
+        RISTRETTO_BASEPOINT_COMPRESSED.to_bytes(),
+        super::batch::BASEPOINT_BYTES
+    );
+}
+
+#[test]
+fn sign_and_verify_fields() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let message = || {
+        MessageBuilder::new(b"example transfer")
+            .field(b"amount", b"100")
+            .field(b"to", b"alice")
+            .field(b"nonce", b"1")
+    };
+
+    let sig = Signature::sign(&mut message().into_transcript(), privkey);
+    assert!(sig.verify(&mut message().into_transcript(), X).is_ok());
+
+    // Omitted field
+    let omitted = MessageBuilder::new(b"example transfer")
+        .field(b"amount", b"100")
+        .field(b"to", b"alice");
+    assert!(sig.verify(&mut omitted.into_transcript(), X).is_err());
+
+    // Reordered fields
+    let reordered = MessageBuilder::new(b"example transfer")
+        .field(b"to", b"alice")
+        .field(b"amount", b"100")
+        .field(b"nonce", b"1");
+    assert!(sig.verify(&mut reordered.into_transcript(), X).is_err());
+
+    // Relabeled field
+    let relabeled = MessageBuilder::new(b"example transfer")
+        .field(b"amount", b"100")
+        .field(b"from", b"alice")
+        .field(b"nonce", b"1");
+    assert!(sig.verify(&mut relabeled.into_transcript(), X).is_err());
+}
+
+#[test]
+fn prehashed_and_raw_messages_differ() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let digest = [7u8; 32];
+    let prehash = PrehashedMessage(digest);
+
+    let prehashed_sig = Signature::sign_prehashed(b"example", &prehash, privkey);
+    let raw_sig = Signature::sign_message(b"example", &digest, privkey);
+
+    assert!(prehashed_sig
+        .verify_prehashed(b"example", &prehash, X)
+        .is_ok());
+    assert!(raw_sig.verify_message(b"example", &digest, X).is_ok());
+
+    assert!(prehashed_sig
+        .verify_message(b"example", &digest, X)
+        .is_err());
+    assert!(raw_sig.verify_prehashed(b"example", &prehash, X).is_err());
+}
+
+#[test]
+fn verify_owned_chain() {
+    let prv1 = Scalar::from(1u64);
+    let prv2 = Scalar::from(2u64);
+    let pub1 = VerificationKey::from_secret(&prv1);
+    let pub2 = VerificationKey::from_secret(&prv2);
+
+    // Second signature signs the transcript state after the first one.
+    let mut prover_transcript = Transcript::new(b"example transcript");
+    let sig1 = Signature::sign(&mut prover_transcript, prv1);
+    let sig2 = Signature::sign(&mut prover_transcript, prv2);
+
+    let t = sig1
+        .verify_owned(Transcript::new(b"example transcript"), pub1)
+        .unwrap();
+    let mut t = sig2.verify_owned(t, pub2).unwrap();
+
+    let mut verifier_challenge = [0u8; 32];
+    let mut prover_challenge = [0u8; 32];
+    t.challenge_bytes(b"test", &mut verifier_challenge);
+    prover_transcript.challenge_bytes(b"test", &mut prover_challenge);
+    assert_eq!(verifier_challenge, prover_challenge);
+
+    // Out of order verification fails.
+    let t = Transcript::new(b"example transcript");
+    assert!(sig2.verify_owned(t, pub2).is_err());
+}
+
+#[test]
+fn forked_transcript() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+
+    let mut main = Transcript::new(b"example protocol");
+    main.append_message(b"step", b"1");
+    let untouched = main.clone();
+
+    let sig = Signature::sign_forked(&main, b"sub-statement", privkey);
+    assert!(sig.verify_forked(&main, b"sub-statement", X).is_ok());
+    assert!(sig.verify_forked(&main, b"other-statement", X).is_err());
+
+    // The forked verification does not affect the main transcript.
+    let mut c1 = [0u8; 32];
+    let mut c2 = [0u8; 32];
+    main.challenge_bytes(b"next", &mut c1);
+    untouched.clone().challenge_bytes(b"next", &mut c2);
+    assert_eq!(c1, c2);
+
+    // The fork is distinct from the main transcript.
+    assert!(sig.verify(&mut untouched.clone(), X).is_err());
+}
+
+#[test]
+fn strict_verification_rejects_degenerate_signature() {
+    // With identity key and nonce, `s = 0` satisfies the equation `s·B == R + c·X`.
+    let identity = VerificationKey::from(CompressedRistretto::default());
+    let degenerate = Signature {
+        s: Scalar::zero(),
+        R: CompressedRistretto::default(),
+    };
+    assert_eq!(
+        degenerate.verify(&mut Transcript::new(b"example transcript"), identity),
+        Err(StarsigError::InvalidKey)
+    );
+    assert_eq!(
+        degenerate.verify_strict(&mut Transcript::new(b"example transcript"), identity),
+        Err(StarsigError::InvalidSignature)
+    );
+
+    // Honest signatures pass the strict verification.
+    let privkey = Scalar::from(1u64);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let X = VerificationKey::from_secret(&privkey);
+    assert!(sig
+        .verify_strict(&mut Transcript::new(b"example transcript"), X)
+        .is_ok());
+}
+
+#[test]
+fn verify_with_stub_transcript() {
+    use super::TranscriptProtocol;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+    use curve25519_dalek::ristretto::CompressedRistretto;
+
+    /// Transcript that ignores all commitments and returns a fixed challenge.
+    struct FixedChallenge(Scalar);
+
+    impl TranscriptProtocol for FixedChallenge {
+        fn starsig_domain_sep(&mut self) {}
+        fn append_scalar(&mut self, _label: &'static [u8], _scalar: &Scalar) {}
+        fn append_point(&mut self, _label: &'static [u8], _point: &CompressedRistretto) {}
+        fn challenge_scalar(&mut self, _label: &'static [u8]) -> Scalar {
+            self.0
+        }
+    }
+
+    let x = Scalar::from(5u64);
+    let r = Scalar::from(7u64);
+    let c = Scalar::from(11u64);
+    let X = VerificationKey::from_secret(&x);
+    let sig = Signature {
+        s: r + c * x,
+        R: (r * RISTRETTO_BASEPOINT_POINT).compress(),
+    };
+
+    assert!(sig.verify(&mut FixedChallenge(c), X).is_ok());
+    assert!(sig
+        .verify(&mut FixedChallenge(c + Scalar::one()), X)
+        .is_err());
+    assert!(sig
+        .verify_point(&mut FixedChallenge(c), x * RISTRETTO_BASEPOINT_POINT)
+        .is_ok());
+}
+
+#[test]
+fn signature_encoding_is_canonical() {
+    let sig = Signature::sign_message(b"message", b"hello", Scalar::from(1u64));
+    let bytes = sig.to_bytes();
+    let decoded = Signature::from_bytes(&bytes[..]).unwrap();
+    assert_eq!(decoded.to_bytes(), bytes);
+
+    // Non-reduced scalar is rejected
+    let mut unreduced = bytes;
+    unreduced[32..].copy_from_slice(&[0xff; 32]);
+    assert!(Signature::from_bytes(&unreduced[..]).is_err());
+}
+
+#[test]
+fn deterministic_nonce_counter() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let seed = [7u8; 32];
+    let sign = |counter| {
+        Signature::sign_deterministic(
+            &mut Transcript::new(b"example transcript"),
+            privkey,
+            &DeterministicNonce::new(seed, counter),
+        )
+    };
+
+    let sig0 = sign(0);
+    let sig1 = sign(1);
+    assert_eq!(sig0.R, sign(0).R);
+    assert_ne!(sig0.R, sig1.R);
+
+    for sig in &[sig0, sig1] {
+        assert!(sig
+            .verify(&mut Transcript::new(b"example transcript"), X)
+            .is_ok());
+    }
+}
+
+#[test]
+fn message_label_must_match() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let base = Transcript::new(b"example transcript");
+
+    let sig = Signature::sign_with_message_label(&base, b"message", b"hello", privkey);
+    assert!(sig
+        .verify_with_message_label(&base, b"message", b"hello", X)
+        .is_ok());
+    assert!(sig
+        .verify_with_message_label(&base, b"payload", b"hello", X)
+        .is_err());
+
+    // Same as committing the message manually
+    let mut t = base.clone();
+    t.append_message(b"message", b"hello");
+    assert!(sig.verify(&mut t, X).is_ok());
+}
+
+#[test]
+fn precomputed_challenge_batch() {
+    use super::TranscriptProtocol;
+    use curve25519_dalek::ristretto::CompressedRistretto;
+
+    let tuples: Vec<(Scalar, VerificationKey, CompressedRistretto, Scalar)> = (1..4u64)
+        .map(|i| {
+            let privkey = Scalar::from(i);
+            let X = VerificationKey::from_secret(&privkey);
+            let mut t = Transcript::new(b"example transcript");
+            t.append_u64(b"i", i);
+            let sig = Signature::sign(&mut t.clone(), privkey);
+            // Extract the challenge the same way the verifier does.
+            t.starsig_domain_sep();
+            t.append_point(b"X", X.as_point());
+            t.append_point(b"R", &sig.R);
+            (t.challenge_scalar(b"c"), X, sig.R, sig.s)
+        })
+        .collect();
+
+    for &(c, X, R, s) in &tuples {
+        assert!(Signature { s, R }.verify_precomputed(c, X).is_ok());
+    }
+    assert!(Signature::verify_precomputed_batch(&tuples).is_ok());
+
+    let mut bad = tuples.clone();
+    bad[1].0 += Scalar::one();
+    assert!(Signature {
+        s: bad[1].3,
+        R: bad[1].2
+    }
+    .verify_precomputed(bad[1].0, bad[1].1)
+    .is_err());
+    assert_eq!(
+        Signature::verify_precomputed_batch(&bad),
+        Err(StarsigError::InvalidBatch)
+    );
+}
+
+#[test]
+fn strict_transcript_requires_message() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+
+    // Signature over the bare transcript, without any message.
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let mut bare = StrictTranscript::new(b"example transcript");
+    assert!(sig
+        .verify(&mut Transcript::new(b"example transcript"), X)
+        .is_ok());
+    assert_eq!(
+        sig.verify_strict_transcript(&mut bare.clone(), X),
+        Err(StarsigError::MessageNotCommitted)
+    );
+    assert_eq!(
+        Signature::sign_strict_transcript(&mut bare, privkey).err(),
+        Some(StarsigError::MessageNotCommitted)
+    );
+
+    let mut t = StrictTranscript::new(b"example transcript");
+    t.commit_message(b"message", b"hello");
+    let sig = Signature::sign_strict_transcript(&mut t.clone(), privkey).unwrap();
+    assert!(sig.verify_strict_transcript(&mut t, X).is_ok());
+}
+
+#[test]
+fn packed_signatures() {
+    let privkeys: Vec<_> = (1..4u64).map(Scalar::from).collect();
+    let keys: Vec<_> = privkeys.iter().map(VerificationKey::from_secret).collect();
+    let messages: Vec<&[u8]> = vec![b"one", b"two", b"three"];
+    let blob: Vec<u8> = privkeys
+        .iter()
+        .zip(messages.iter())
+        .flat_map(|(x, m)| {
+            Signature::sign_message(b"packed", m, *x)
+                .to_bytes()
+                .to_vec()
+        })
+        .collect();
+    assert_eq!(blob.len(), 3 * 64);
+
+    assert!(Signature::verify_packed(b"packed", &blob, &keys, &messages).is_ok());
+
+    // Wrong message
+    let wrong = vec![messages[0], messages[2], messages[1]];
+    assert!(Signature::verify_packed(b"packed", &blob, &keys, &wrong).is_err());
+
+    // Length not divisible by 64 and mismatched counts
+    assert_eq!(
+        Signature::verify_packed(b"packed", &blob[..100], &keys, &messages),
+        Err(StarsigError::InvalidSignature)
+    );
+    assert_eq!(
+        Signature::verify_packed(b"packed", &blob, &keys[..2], &messages),
+        Err(StarsigError::InvalidSignature)
+    );
+    assert_eq!(
+        Signature::verify_packed(b"packed", &blob[..128], &keys[..2], &messages),
+        Err(StarsigError::InvalidSignature)
+    );
+}
+
+#[test]
+fn random_signatures_never_panic() {
+    use curve25519_dalek::ristretto::CompressedRistretto;
+    use rand::RngCore;
+
+    let mut rng = rand::thread_rng();
+    let X = VerificationKey::from_secret(&Scalar::from(1u64));
+    for _ in 0..256 {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+
+        // Decoding rejects non-canonical scalars, but a signature with arbitrary contents
+        // can still be constructed directly.
+        let _ = Signature::from_bytes(&bytes[..]).map(|sig| {
+            let _ = sig.verify(&mut Transcript::new(b"example transcript"), X);
+        });
+        let mut R = [0u8; 32];
+        let mut s = [0u8; 32];
+        R.copy_from_slice(&bytes[..32]);
+        s.copy_from_slice(&bytes[32..]);
+        let sig = Signature {
+            s: Scalar::from_bits(s),
+            R: CompressedRistretto(R),
+        };
+        assert!(sig
+            .verify(&mut Transcript::new(b"example transcript"), X)
+            .is_err());
+        assert!(sig
+            .verify_strict(&mut Transcript::new(b"example transcript"), X)
+            .is_err());
+        let _ = sig.verify_message(b"message", b"hello", X);
+    }
+}
+
+#[test]
+fn batch_mask() {
+    use super::TranscriptProtocol;
+
+    let mut tuples: Vec<_> = (1..6u64)
+        .map(|i| {
+            let privkey = Scalar::from(i);
+            let X = VerificationKey::from_secret(&privkey);
+            let mut t = Transcript::new(b"example transcript");
+            let sig = Signature::sign(&mut t.clone(), privkey);
+            t.starsig_domain_sep();
+            t.append_point(b"X", X.as_point());
+            t.append_point(b"R", &sig.R);
+            (t.challenge_scalar(b"c"), X, sig.R, sig.s)
+        })
+        .collect();
+    assert_eq!(Signature::verify_batch_mask(&tuples), vec![true; 5]);
+
+    tuples[1].3 += Scalar::one();
+    tuples[3].0 += Scalar::one();
+    assert_eq!(
+        Signature::verify_batch_mask(&tuples),
+        vec![true, false, true, false, true]
+    );
+}
+
+#[test]
+fn identity_key_never_verifies() {
+    use curve25519_dalek::ristretto::CompressedRistretto;
+    use curve25519_dalek::traits::Identity;
+
+    let X = VerificationKey::IDENTITY;
+    assert_eq!(X, VerificationKey::from(RistrettoPoint::identity()));
+
+    // Degenerate signature that satisfies the verification equation for the identity key.
+    let sig = Signature {
+        s: Scalar::zero(),
+        R: CompressedRistretto::default(),
+    };
+    assert_eq!(
+        sig.verify(&mut Transcript::new(b"example transcript"), X),
+        Err(StarsigError::InvalidKey)
+    );
+    assert_eq!(
+        sig.verify_point(
+            &mut Transcript::new(b"example transcript"),
+            RistrettoPoint::identity()
+        ),
+        Err(StarsigError::InvalidKey)
+    );
+    assert_eq!(
+        sig.verify_precomputed(Scalar::one(), X),
+        Err(StarsigError::InvalidKey)
+    );
+
+    let mut batch = BatchVerifier::new(rand::thread_rng());
+    sig.verify_batched(&mut Transcript::new(b"example transcript"), X, &mut batch);
+    assert_eq!(batch.verify(), Err(StarsigError::InvalidBatch));
+}
+
+#[test]
+fn hedged_signatures() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig1 = Signature::sign_hedged(
+        &mut Transcript::new(b"example transcript"),
+        privkey,
+        b"entropy 1",
+    );
+    let sig2 = Signature::sign_hedged(
+        &mut Transcript::new(b"example transcript"),
+        privkey,
+        b"entropy 2",
+    );
+    assert_ne!(sig1.R, sig2.R);
+    for sig in &[sig1, sig2] {
+        assert!(sig
+            .verify(&mut Transcript::new(b"example transcript"), X)
+            .is_ok());
+    }
+}
+
+#[test]
+fn verify_consumes_transcript_state() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let mut prover_transcript = Transcript::new(b"example transcript");
+    let sig = Signature::sign(&mut prover_transcript, privkey);
+
+    // Verification advances the caller's transcript just like signing does.
+    let mut verifier_transcript = Transcript::new(b"example transcript");
+    assert!(sig.verify(&mut verifier_transcript, X).is_ok());
+    let mut prover_challenge = [0u8; 32];
+    let mut verifier_challenge = [0u8; 32];
+    prover_transcript.challenge_bytes(b"test", &mut prover_challenge);
+    verifier_transcript.challenge_bytes(b"test", &mut verifier_challenge);
+    assert_eq!(prover_challenge, verifier_challenge);
+
+    // Reusing the consumed transcript for the same signature fails.
+    let mut t = Transcript::new(b"example transcript");
+    assert!(sig.verify(&mut t, X).is_ok());
+    assert!(sig.verify(&mut t, X).is_err());
+}
+
+#[test]
+fn simple_verification() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+
+    // Manual transcript flow
+    let mut t = Transcript::new(b"example domain");
+    t.append_message(b"message", b"hello");
+    let sig = Signature::sign(&mut t, privkey);
+
+    assert!(sig.verify_simple(b"hello", b"example domain", X).is_ok());
+    assert!(sig.verify_simple(b"hullo", b"example domain", X).is_err());
+    assert!(sig.verify_simple(b"hello", b"other domain", X).is_err());
+
+    let sig = Signature::sign_simple(b"example domain", b"hello", privkey).unwrap();
+    let mut t = Transcript::new(b"example domain");
+    t.append_message(b"message", b"hello");
+    assert!(sig.verify(&mut t, X).is_ok());
+}
+
+#[test]
+fn challenge_uses_public_labels() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+
+    // Reconstruct the challenge with raw Merlin calls and the exported labels.
+    let mut t = Transcript::new(b"example transcript");
+    t.append_message(LABEL_DOMAIN_SEP, DOMAIN_SEP);
+    t.append_message(LABEL_KEY, X.as_bytes());
+    t.append_message(LABEL_NONCE, sig.R.as_bytes());
+    let mut buf = [0u8; 64];
+    t.challenge_bytes(LABEL_CHALLENGE, &mut buf);
+    let c = Scalar::from_bytes_mod_order_wide(&buf);
+
+    assert!(sig.verify_precomputed(c, X).is_ok());
+    assert!(sig.verify_precomputed(c + Scalar::one(), X).is_err());
+}
+
+#[test]
+fn epoch_signatures() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig = Signature::sign_with_epoch(b"example domain", 1, b"message", privkey).unwrap();
+
+    assert!(sig
+        .verify_with_epoch(b"example domain", 1, b"message", X)
+        .is_ok());
+    assert!(sig
+        .verify_with_epoch(b"example domain", 2, b"message", X)
+        .is_err());
+    assert!(sig.verify_simple(b"message", b"example domain", X).is_err());
+}
+
+#[test]
+fn verify_report_warnings() {
+    // Secret key 1 makes the verification key equal to the base point.
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    assert_eq!(
+        sig.verify_report(&mut Transcript::new(b"example transcript"), X),
+        VerifyOutcome::ValidWithWarnings(vec![Warning::BasepointKey])
+    );
+
+    let privkey = Scalar::from(2u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    assert_eq!(
+        sig.verify_report(&mut Transcript::new(b"example transcript"), X),
+        VerifyOutcome::Valid
+    );
+    assert_eq!(
+        sig.verify_report(&mut Transcript::new(b"other transcript"), X),
+        VerifyOutcome::Invalid(StarsigError::InvalidSignature)
+    );
+}
+
+#[test]
+fn key_endorsement() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let endorsed = VerificationKey::from_secret(&Scalar::from(2u64));
+    let other = VerificationKey::from_secret(&Scalar::from(3u64));
+
+    let sig = Signature::sign_endorsement(
+        &mut Transcript::new(b"example transcript"),
+        privkey,
+        endorsed,
+    );
+    assert!(sig
+        .verify_endorsement(&mut Transcript::new(b"example transcript"), X, endorsed)
+        .is_ok());
+    assert!(sig
+        .verify_endorsement(&mut Transcript::new(b"example transcript"), X, other)
+        .is_err());
+
+    // Signature over the key bytes as data is not an endorsement.
+    let mut t = Transcript::new(b"example transcript");
+    t.append_message(b"message", endorsed.as_bytes());
+    let data_sig = Signature::sign(&mut t, privkey);
+    assert!(data_sig
+        .verify_endorsement(&mut Transcript::new(b"example transcript"), X, endorsed)
+        .is_err());
+}
+
+#[test]
+fn verify_then_derive_bytes() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig1 = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let sig2 = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+
+    let mut key1 = [0u8; 32];
+    let mut key2 = [0u8; 32];
+    assert!(sig1
+        .verify_then_challenge_bytes(
+            &mut Transcript::new(b"example transcript"),
+            X,
+            b"session-key",
+            &mut key1
+        )
+        .is_ok());
+    assert!(sig2
+        .verify_then_challenge_bytes(
+            &mut Transcript::new(b"example transcript"),
+            X,
+            b"session-key",
+            &mut key2
+        )
+        .is_ok());
+    assert_ne!(key1, key2);
+
+    let mut key3 = [0u8; 32];
+    assert!(sig1
+        .verify_then_challenge_bytes(
+            &mut Transcript::new(b"other transcript"),
+            X,
+            b"session-key",
+            &mut key3
+        )
+        .is_err());
+    assert_eq!(key3, [0u8; 32]);
+}
+
+#[test]
+fn signing_transcript_vector() {
+    let privkey = Scalar::from(1u64);
+    let sig = Signature::sign_deterministic(
+        &mut Signature::signing_transcript(b"Starsig.example", b"hello world"),
+        privkey,
+        &DeterministicNonce::new([7u8; 32], 0),
+    );
+    assert_eq!(
+        hex::encode(&sig.to_bytes()[..]),
+        "867f80ff154737a602ee762d37862cfbb4363a6905768871359f4b0ebddbed46\
+         5f50f43d174937cd1ac13f279a3b8553d12e5e83f205a7fab080a044978b080c"
+    );
+    assert!(sig
+        .verify_simple(
+            b"hello world",
+            b"Starsig.example",
+            VerificationKey::from_secret(&privkey)
+        )
+        .is_ok());
+}
+
+#[test]
+fn verification_keys_sort_by_bytes() {
+    let mut keys: Vec<_> = (1..10u64)
+        .map(|i| VerificationKey::from_secret(&Scalar::from(i)))
+        .collect();
+    keys.sort();
+
+    let mut bytes: Vec<_> = keys.iter().map(|k| k.to_bytes()).collect();
+    assert!(bytes.windows(2).all(|w| w[0] < w[1]));
+    bytes.sort();
+    assert_eq!(keys.iter().map(|k| k.to_bytes()).collect::<Vec<_>>(), bytes);
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_verify_message() {
+    use super::{starsig_verify_message, FFI_MESSAGE_LABEL};
+
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let msg = b"hello world";
+    let sig = Signature::sign_message(FFI_MESSAGE_LABEL, msg, privkey).to_bytes();
+    let key = X.to_bytes();
+
+    unsafe {
+        assert_eq!(
+            starsig_verify_message(sig.as_ptr(), key.as_ptr(), msg.as_ptr(), msg.len()),
+            1
+        );
+        assert_eq!(
+            starsig_verify_message(sig.as_ptr(), key.as_ptr(), msg.as_ptr(), msg.len() - 1),
+            0
+        );
+        assert_eq!(
+            starsig_verify_message(core::ptr::null(), key.as_ptr(), msg.as_ptr(), msg.len()),
+            -1
+        );
+    }
+}
+
+#[test]
+fn committed_and_committing_verification() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let committed = || {
+        let mut t = Transcript::new(b"example transcript");
+        t.append_message(b"message", b"hello world");
+        t
+    };
+    let sig = Signature::sign(&mut committed(), privkey);
+
+    assert!(sig
+        .verify_on_committed_transcript(&mut committed(), X)
+        .is_ok());
+    assert!(sig
+        .verify_committing_message(
+            &mut Transcript::new(b"example transcript"),
+            b"message",
+            b"hello world",
+            X
+        )
+        .is_ok());
+
+    // Committing the message twice, or not at all, fails.
+    assert!(sig
+        .verify_committing_message(&mut committed(), b"message", b"hello world", X)
+        .is_err());
+    assert!(sig
+        .verify_on_committed_transcript(&mut Transcript::new(b"example transcript"), X)
+        .is_err());
+}
+
+#[test]
+fn keys_are_torsion_free() {
+    for i in 1..10u64 {
+        let privkey = Scalar::from(i);
+        let X = VerificationKey::from_secret(&privkey);
+        assert!(X.is_torsion_free());
+
+        let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+        assert!(sig
+            .verify_strict(&mut Transcript::new(b"example transcript"), X)
+            .is_ok());
+    }
+    let bad_key = VerificationKey::from_compressed(CompressedRistretto([0xff; 32]));
+    assert!(!bad_key.is_torsion_free());
+}
+
+#[test]
+fn verification_witness() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let witness = sig
+        .verify_with_witness(&mut Transcript::new(b"example transcript"), X)
+        .unwrap();
+
+    // s·G == R + c·X
+    let R = witness.R.decompress().unwrap();
+    let X_point = witness.X.decompress().unwrap();
+    assert_eq!(
+        RISTRETTO_BASEPOINT_POINT * witness.s,
+        R + X_point * witness.c
+    );
+    assert_eq!(&witness.to_bytes()[..32], witness.c.as_bytes());
+    assert_eq!(&witness.to_bytes()[96..], X.as_bytes());
+
+    assert!(sig
+        .verify_with_witness(&mut Transcript::new(b"other transcript"), X)
+        .is_err());
+}
+
+#[test]
+fn challenge_layouts() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let t = Transcript::new(b"example transcript");
+
+    let sig = Signature::sign_with_layout(&mut t.clone(), privkey, ChallengeLayout::NonceFirst);
+    assert!(sig
+        .verify_with_layout(&mut t.clone(), X, ChallengeLayout::NonceFirst)
+        .is_ok());
+    assert!(sig
+        .verify_with_layout(&mut t.clone(), X, ChallengeLayout::KeyFirst)
+        .is_err());
+    assert!(sig.verify(&mut t.clone(), X).is_err());
+
+    // Default layout is the one used by `sign` and `verify`.
+    let sig = Signature::sign(&mut t.clone(), privkey);
+    assert!(sig
+        .verify_with_layout(&mut t.clone(), X, ChallengeLayout::default())
+        .is_ok());
+    assert!(sig
+        .verify_with_layout(&mut t.clone(), X, ChallengeLayout::NonceFirst)
+        .is_err());
+}
+
+#[test]
+fn verify_split_encoding() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let t = Transcript::new(b"example transcript");
+    let sig = Signature::sign(&mut t.clone(), privkey);
+
+    // The header carries R, the body carries s.
+    let header = sig.R;
+    let body = sig.s;
+    assert_eq!(
+        Signature::verify_split(header, body, &mut t.clone(), X),
+        sig.verify(&mut t.clone(), X)
+    );
+    assert!(Signature::verify_split(header, body, &mut t.clone(), X).is_ok());
+
+    // Mixing the parts of different signatures fails like the reassembled signature does.
+    let other = Signature::sign(&mut Transcript::new(b"other transcript"), privkey);
+    let mixed = Signature {
+        s: other.s,
+        R: header,
+    };
+    assert_eq!(
+        Signature::verify_split(header, other.s, &mut t.clone(), X),
+        mixed.verify(&mut t.clone(), X)
+    );
+    assert!(Signature::verify_split(header, other.s, &mut t.clone(), X).is_err());
+}
+
+#[test]
+fn commit_field_is_unambiguous() {
+    use super::TranscriptProtocol;
+
+    let fields = |a: &[u8], b: &[u8]| {
+        let mut t = Transcript::new(b"example transcript");
+        t.commit_field(b"field", a);
+        t.commit_field(b"field", b);
+        let mut out = [0u8; 32];
+        t.challenge_bytes(b"out", &mut out);
+        out
+    };
+    assert_ne!(fields(b"ab", b"c"), fields(b"a", b"bc"));
+    assert_eq!(fields(b"ab", b"c"), fields(b"ab", b"c"));
+
+    /// Transcript that records the committed bytes, using the default `commit_field`.
+    #[derive(Default)]
+    struct Recorder(Vec<Vec<u8>>);
+
+    impl TranscriptProtocol for Recorder {
+        fn starsig_domain_sep(&mut self) {}
+        fn append_scalar(&mut self, _label: &'static [u8], scalar: &Scalar) {
+            self.0.push(scalar.to_bytes().to_vec());
+        }
+        fn append_point(&mut self, _label: &'static [u8], point: &CompressedRistretto) {
+            self.0.push(point.to_bytes().to_vec());
+        }
+        fn challenge_scalar(&mut self, _label: &'static [u8]) -> Scalar {
+            Scalar::zero()
+        }
+    }
+
+    let recorded = |a: &[u8], b: &[u8]| {
+        let mut t = Recorder::default();
+        t.commit_field(b"field", a);
+        t.commit_field(b"field", b);
+        t.0
+    };
+    assert_ne!(recorded(b"ab", b"c"), recorded(b"a", b"bc"));
+    assert_ne!(recorded(b"a", b""), recorded(b"a\0", b""));
+    assert_eq!(recorded(&[7u8; 33], b"").len(), 4);
+
+    // The signing helpers commit the message as a field.
+    let mut t = Transcript::new(b"Starsig.example");
+    t.commit_field(b"message", b"hello world");
+    let privkey = Scalar::from(1u64);
+    let sig = Signature::sign(&mut t, privkey);
+    assert!(sig
+        .verify_simple(
+            b"hello world",
+            b"Starsig.example",
+            VerificationKey::from_secret(&privkey)
+        )
+        .is_ok());
+}
+
+#[test]
+fn shared_nonce_batch() {
+    // Legacy format: every signer uses the same nonce `r` over the same message.
+    let transcript = Transcript::new(b"example transcript");
+    let r = Scalar::from(42u64);
+    let R = (r * RISTRETTO_BASEPOINT_POINT).compress();
+    let privkeys = [Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64)];
+    let items: Vec<_> = privkeys
+        .iter()
+        .map(|x| {
+            let X = VerificationKey::from_secret(x);
+            let mut t = transcript.clone();
+            let c = ChallengeLayout::KeyFirst.challenge(&mut t, X.as_point(), &R);
+            (X, r + c * x)
+        })
+        .collect();
+    assert!(Signature::verify_shared_nonce(R, &items, &transcript).is_ok());
+
+    // Each item is a regular signature with the shared nonce.
+    for (X, s) in &items {
+        assert!(Signature { s: *s, R }
+            .verify(&mut transcript.clone(), *X)
+            .is_ok());
+    }
+
+    // Different message, a tampered scalar or a different nonce fail.
+    assert!(
+        Signature::verify_shared_nonce(R, &items, &Transcript::new(b"other transcript")).is_err()
+    );
+    let mut tampered = items.clone();
+    tampered[1].1 += Scalar::one();
+    assert!(Signature::verify_shared_nonce(R, &tampered, &transcript).is_err());
+    let other_R = (Scalar::from(43u64) * RISTRETTO_BASEPOINT_POINT).compress();
+    assert!(Signature::verify_shared_nonce(other_R, &items, &transcript).is_err());
+    assert_eq!(
+        Signature::verify_shared_nonce(R, &[], &transcript),
+        Err(StarsigError::InvalidBatch)
+    );
+}
+
+#[test]
+fn signature_proof_round_trip() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let t = Transcript::new(b"example transcript");
+    let sig = Signature::sign(&mut t.clone(), privkey);
+
+    let proof = SignatureProof::prove(&sig, &t, X).unwrap();
+    assert!(proof.verify(&t, X).is_ok());
+
+    let decoded = SignatureProof::from_bytes(&proof.to_bytes()).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(decoded.verify(&t, X).is_ok());
+
+    // The encoding does not contain the signature scalar.
+    let bytes = proof.to_bytes();
+    assert!(!bytes.windows(32).any(|w| w == sig.s.as_bytes()));
+}
+
+#[test]
+fn signature_proof_soundness() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let t = Transcript::new(b"example transcript");
+    let sig = Signature::sign(&mut t.clone(), privkey);
+    let proof = SignatureProof::prove(&sig, &t, X).unwrap();
+
+    // Invalid signatures cannot be proven.
+    let forged = Signature {
+        s: sig.s + Scalar::one(),
+        R: sig.R,
+    };
+    assert!(SignatureProof::prove(&forged, &t, X).is_err());
+
+    // The proof is bound to the message and the key.
+    let other_X = VerificationKey::from_secret(&Scalar::from(2u64));
+    assert!(proof
+        .verify(&Transcript::new(b"other transcript"), X)
+        .is_err());
+    assert!(proof.verify(&t, other_X).is_err());
+
+    // Any tampered component fails to verify.
+    let mut bytes = proof.to_bytes();
+    for i in &[0, 32, 64] {
+        let mut tampered = bytes;
+        tampered[*i] ^= 1;
+        if let Ok(tampered) = SignatureProof::from_bytes(&tampered) {
+            assert!(tampered.verify(&t, X).is_err());
+        }
+    }
+
+    // The response for a different nonce does not verify.
+    let other_sig = Signature::sign(&mut t.clone(), privkey);
+    bytes[..32].copy_from_slice(other_sig.R.as_bytes());
+    assert!(SignatureProof::from_bytes(&bytes)
+        .unwrap()
+        .verify(&t, X)
+        .is_err());
+}
+
+#[test]
+fn verify_batch_from_iterator() {
+    let privkeys: Vec<Scalar> = (1..=8u64).map(Scalar::from).collect();
+    let sign_item = |x: &Scalar| {
+        let t = Transcript::new(b"example transcript");
+        let sig = Signature::sign(&mut t.clone(), *x);
+        (t, VerificationKey::from_secret(x), sig)
+    };
+    let items: Vec<_> = privkeys.iter().map(sign_item).collect();
+
+    // Same result as the slice-based batch verifier.
+    let mut batch = BatchVerifier::new(rand::thread_rng());
+    for (t, X, sig) in &items {
+        sig.verify_batched(&mut t.clone(), *X, &mut batch);
+    }
+    assert_eq!(batch.verify(), Ok(()));
+    assert_eq!(Signature::verify_batch_iter(items.clone()), Ok(()));
+
+    // The items can be produced lazily, without knowing the count up front.
+    let stream = privkeys.iter().step_by(2).map(sign_item);
+    assert_eq!(Signature::verify_batch_iter(stream), Ok(()));
+
+    // A single bad item fails the whole stream.
+    let mut bad = items;
+    bad[3].0 = Transcript::new(b"other transcript");
+    assert_eq!(
+        Signature::verify_batch_iter(bad),
+        Err(StarsigError::InvalidBatch)
+    );
+    assert_eq!(Signature::verify_batch_iter(Vec::new()), Ok(()));
+}
+
+#[cfg(feature = "debug-tools")]
+#[test]
+fn verify_difference() {
+    use curve25519_dalek::traits::IsIdentity;
+
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let t = Transcript::new(b"example transcript");
+    let sig = Signature::sign(&mut t.clone(), privkey);
+
+    let D = sig.verify_difference(&mut t.clone(), X).unwrap();
+    assert!(D.decompress().unwrap().is_identity());
+
+    // A tampered scalar shifts the difference by exactly the tampering.
+    let tampered = Signature {
+        s: sig.s + Scalar::one(),
+        R: sig.R,
+    };
+    let D = tampered.verify_difference(&mut t.clone(), X).unwrap();
+    assert!(!D.decompress().unwrap().is_identity());
+    assert_eq!(D, RISTRETTO_BASEPOINT_POINT.compress());
+}
+
+#[test]
+fn generate_private_key() {
+    let mut rng = rand::thread_rng();
+    let keys: Vec<_> = (0..16).map(|_| PrivateKey::generate(&mut rng)).collect();
+    for key in &keys {
+        assert_ne!(*key.as_scalar(), Scalar::zero());
+        assert_eq!(
+            Scalar::from_canonical_bytes(key.as_scalar().to_bytes()),
+            Some(*key.as_scalar())
+        );
+
+        // The key signs messages that verify against its verification key.
+        let sig = Signature::sign_message(b"message", b"hello world", *key.as_scalar());
+        assert!(sig
+            .verify_message(b"message", b"hello world", key.verification_key())
+            .is_ok());
+    }
+    assert_ne!(keys[0].as_scalar(), keys[1].as_scalar());
+}
+
+#[test]
+fn verify_with_nonces() {
+    // Two parties contribute the nonces `r_1` and `r_2`, and the signature uses their sum.
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let t = Transcript::new(b"example transcript");
+    let (r_1, r_2) = (Scalar::from(10u64), Scalar::from(20u64));
+    let nonces = [
+        (r_1 * RISTRETTO_BASEPOINT_POINT).compress(),
+        (r_2 * RISTRETTO_BASEPOINT_POINT).compress(),
+    ];
+    let R = ((r_1 + r_2) * RISTRETTO_BASEPOINT_POINT).compress();
+    let c = ChallengeLayout::KeyFirst.challenge(&mut t.clone(), X.as_point(), &R);
+    let sig = Signature {
+        s: r_1 + r_2 + c * privkey,
+        R,
+    };
+    assert!(sig.verify(&mut t.clone(), X).is_ok());
+    assert!(sig.verify_with_nonces(&mut t.clone(), X, &nonces).is_ok());
+
+    // A valid signature whose `R` is not the sum of the nonces is rejected.
+    let other_nonces = [
+        nonces[0],
+        (Scalar::from(21u64) * RISTRETTO_BASEPOINT_POINT).compress(),
+    ];
+    assert_eq!(
+        sig.verify_with_nonces(&mut t.clone(), X, &other_nonces),
+        Err(StarsigError::InvalidSignature)
+    );
+    assert!(sig
+        .verify_with_nonces(&mut t.clone(), X, &nonces[..1])
+        .is_err());
+    assert!(sig.verify_with_nonces(&mut t.clone(), X, &[]).is_err());
+}
+
+#[test]
+fn dynamic_domain() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let chain_id = 7u32;
+    let domain = format!("Starsig.chain-{}", chain_id);
+
+    let sig = Signature::sign_simple_dynamic(domain.as_bytes(), b"hello world", privkey).unwrap();
+    assert!(sig
+        .verify_simple_dynamic(b"hello world", domain.as_bytes(), X)
+        .is_ok());
+
+    // Another runtime domain, or the same domain as a static label, does not verify.
+    let other = format!("Starsig.chain-{}", chain_id + 1);
+    assert!(sig
+        .verify_simple_dynamic(b"hello world", other.as_bytes(), X)
+        .is_err());
+    assert!(sig
+        .verify_simple(b"hello world", b"Starsig.chain-7", X)
+        .is_err());
+}
+
+#[test]
+fn verify_detailed_failures() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let bad_point = CompressedRistretto([0xff; 32]);
+
+    assert_eq!(
+        sig.verify_detailed(&mut Transcript::new(b"example transcript"), X),
+        Ok(())
+    );
+
+    let bad_nonce = Signature {
+        R: bad_point,
+        ..sig
+    };
+    assert_eq!(
+        bad_nonce.verify_detailed(&mut Transcript::new(b"example transcript"), X),
+        Err(VerifyFailure::BadNonce)
+    );
+
+    let bad_key = VerificationKey::from_compressed(bad_point);
+    assert_eq!(
+        sig.verify_detailed(&mut Transcript::new(b"example transcript"), bad_key),
+        Err(VerifyFailure::BadKey)
+    );
+    assert_eq!(
+        sig.verify_detailed(
+            &mut Transcript::new(b"example transcript"),
+            VerificationKey::IDENTITY
+        ),
+        Err(VerifyFailure::BadKey)
+    );
+
+    assert_eq!(
+        sig.verify_detailed(&mut Transcript::new(b"other transcript"), X),
+        Err(VerifyFailure::EquationMismatch)
+    );
+}
+
+#[test]
+fn signature_array_conversions() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let bytes = sig.to_bytes();
+
+    let converted = Signature::from(bytes);
+    assert_eq!(converted.to_bytes(), bytes);
+    assert!(converted
+        .verify_strict(&mut Transcript::new(b"example transcript"), X)
+        .is_ok());
+
+    let decoded = Signature::try_from(&bytes[..]).unwrap();
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert_eq!(
+        Signature::try_from(&bytes[..63]).err(),
+        Some(StarsigError::InvalidSignature)
+    );
+
+    // The top bit of `s` does not fit in a scalar and is cleared by the conversion.
+    let mut high = bytes;
+    high[63] |= 0x80;
+    let converted = Signature::from(high);
+    assert_ne!(converted.to_bytes(), high);
+    assert_eq!(converted.to_bytes(), bytes);
+}
+
+#[test]
+fn dleq_round_trip() {
+    let x = Scalar::from(7u64);
+    let G = RISTRETTO_BASEPOINT_POINT;
+    let H = Scalar::from(11u64) * RISTRETTO_BASEPOINT_POINT;
+    let proof = dleq::prove(&mut Transcript::new(b"dleq test"), x, &G, &H);
+
+    assert!(dleq::verify(
+        &mut Transcript::new(b"dleq test"),
+        &(x * G),
+        &(x * H),
+        &G,
+        &H,
+        &proof
+    )
+    .is_ok());
+    let decoded = DleqProof::from_bytes(&proof.to_bytes()).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(dleq::verify(
+        &mut Transcript::new(b"other transcript"),
+        &(x * G),
+        &(x * H),
+        &G,
+        &H,
+        &proof
+    )
+    .is_err());
+}
+
+#[test]
+fn dleq_soundness() {
+    let x = Scalar::from(7u64);
+    let y = Scalar::from(8u64);
+    let G = RISTRETTO_BASEPOINT_POINT;
+    let H = Scalar::from(11u64) * RISTRETTO_BASEPOINT_POINT;
+    let proof = dleq::prove(&mut Transcript::new(b"dleq test"), x, &G, &H);
+
+    // The proof for `x` does not show equality with a point of a different log.
+    assert_eq!(
+        dleq::verify(
+            &mut Transcript::new(b"dleq test"),
+            &(x * G),
+            &(y * H),
+            &G,
+            &H,
+            &proof
+        ),
+        Err(StarsigError::InvalidSignature)
+    );
+    // And the bases cannot be swapped.
+    assert!(dleq::verify(
+        &mut Transcript::new(b"dleq test"),
+        &(x * H),
+        &(x * G),
+        &H,
+        &G,
+        &proof
+    )
+    .is_err());
+}
+
+#[test]
+fn empty_domain_is_rejected() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+
+    let sig = Signature::sign(&mut Transcript::new(b""), privkey);
+    assert_eq!(
+        sig.verify_simple(b"hello", b"", X),
+        Err(StarsigError::EmptyDomain)
+    );
+    assert_eq!(
+        sig.verify_simple_dynamic(b"hello", b"", X),
+        Err(StarsigError::EmptyDomain)
+    );
+    assert_eq!(
+        sig.verify_with_epoch(b"", 1, b"hello", X),
+        Err(StarsigError::EmptyDomain)
+    );
+    assert_eq!(
+        sig.verify_with_role(b"", b"proposer", b"hello", X),
+        Err(StarsigError::EmptyDomain)
+    );
+
+    // The low-level API stays permissive.
+    assert!(sig.verify(&mut Transcript::new(b""), X).is_ok());
+}
+
+#[test]
+fn empty_domain_is_rejected_when_signing() {
+    let privkey = Scalar::from(1u64);
+
+    assert_eq!(
+        Signature::sign_simple(b"", b"hello", privkey).err(),
+        Some(StarsigError::EmptyDomain)
+    );
+    assert_eq!(
+        Signature::sign_simple_dynamic(b"", b"hello", privkey).err(),
+        Some(StarsigError::EmptyDomain)
+    );
+    assert_eq!(
+        Signature::sign_with_epoch(b"", 1, b"hello", privkey).err(),
+        Some(StarsigError::EmptyDomain)
+    );
+    assert_eq!(
+        Signature::sign_with_role(b"", b"proposer", b"hello", privkey).err(),
+        Some(StarsigError::EmptyDomain)
+    );
+}
+
+#[test]
+fn verify_sequential_stops_at_first_failure() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let items: Vec<_> = (0..5u64)
+        .map(|i| {
+            let mut t = Transcript::new(b"sequential");
+            t.append_u64(b"i", i);
+            let sig = Signature::sign(&mut t.clone(), privkey);
+            // Item 2 is verified against another transcript, and so is item 4.
+            if i == 2 || i == 4 {
+                t.append_u64(b"i", i);
+            }
+            (t, X, sig)
+        })
+        .collect();
+
+    assert!(Signature::verify_sequential(items[..2].to_vec()).is_ok());
+
+    let mut consumed = 0;
+    let result = Signature::verify_sequential(items.iter().cloned().inspect(|_| consumed += 1));
+    assert_eq!(result, Err((2, StarsigError::InvalidSignature)));
+    assert_eq!(consumed, 3);
+}
+
+#[test]
+fn signing_roles() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+
+    let sig = Signature::sign_with_role(b"consensus", b"proposer", b"block 7", privkey).unwrap();
+    assert!(sig
+        .verify_with_role(b"consensus", b"proposer", b"block 7", X)
+        .is_ok());
+    assert_eq!(
+        sig.verify_with_role(b"consensus", b"attester", b"block 7", X),
+        Err(StarsigError::InvalidSignature)
+    );
+    // The role is not just a prefix of the message.
+    assert!(sig
+        .verify_simple(b"proposerblock 7", b"consensus", X)
+        .is_err());
+}
+
+#[test]
+fn transcript_state_round_trip() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let mut state = TranscriptState::new(b"payments");
+    state.commit_field(b"recipient", b"alice");
+    state.commit_field(b"amount", &100u64.to_le_bytes());
+    let sig = Signature::sign(&mut state.to_transcript(), privkey);
+
+    // The verifier receives the state as bytes and restores it.
+    let restored = TranscriptState::from_bytes(&state.to_bytes()).unwrap();
+    assert_eq!(restored, state);
+    assert!(sig.verify(&mut restored.to_transcript(), X).is_ok());
+
+    let mut other = restored.clone();
+    other.commit_field(b"memo", b"");
+    assert!(sig.verify(&mut other.to_transcript(), X).is_err());
+
+    let bytes = state.to_bytes();
+    assert_eq!(
+        TranscriptState::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(StarsigError::InvalidTranscriptState)
+    );
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert_eq!(
+        TranscriptState::from_bytes(&extended),
+        Err(StarsigError::InvalidTranscriptState)
+    );
+}
+
+#[test]
+fn verify_shared_message_per_signer() {
+    let privkeys: Vec<_> = (1..=4u64).map(Scalar::from).collect();
+    let mut items: Vec<_> = privkeys
+        .iter()
+        .map(|x| {
+            (
+                VerificationKey::from_secret(x),
+                Signature::sign_simple(b"committee", b"block 7", *x).unwrap(),
+            )
+        })
+        .collect();
+    // Signer 1 attested another message, and signer 3's signature is attributed to another key.
+    items[1].1 = Signature::sign_simple(b"committee", b"block 8", privkeys[1]).unwrap();
+    items[3].0 = VerificationKey::from_secret(&Scalar::from(5u64));
+
+    assert_eq!(
+        Signature::verify_shared_message(b"block 7", b"committee", &items),
+        Ok(vec![true, false, true, false])
+    );
+    assert_eq!(
+        Signature::verify_shared_message(b"block 7", b"committee", &[]),
+        Ok(vec![])
+    );
+    assert_eq!(
+        Signature::verify_shared_message(b"block 7", b"", &items),
+        Err(StarsigError::EmptyDomain)
+    );
+}
+
+#[test]
+fn custom_generator() {
+    // Generator hashed to the curve, with unknown discrete log w.r.t. the base point.
+    let mut t = Transcript::new(b"generator");
+    let mut bytes = [0u8; 64];
+    t.challenge_bytes(b"H", &mut bytes);
+    let H = RistrettoPoint::from_uniform_bytes(&bytes);
+
+    let privkey = Scalar::from(3u64);
+    let X = VerificationKey::from(privkey * H);
+    let sig = Signature::sign_with_generator(&mut Transcript::new(b"pedersen"), privkey, H);
+    assert!(sig
+        .verify_with_generator(&mut Transcript::new(b"pedersen"), X, H)
+        .is_ok());
+
+    // The signature is bound to the generator.
+    assert!(sig
+        .verify_with_generator(
+            &mut Transcript::new(b"pedersen"),
+            X,
+            RISTRETTO_BASEPOINT_POINT
+        )
+        .is_err());
+    assert!(sig
+        .verify_with_generator(&mut Transcript::new(b"other"), X, H)
+        .is_err());
+}
+
+#[test]
+fn interactive_identification() {
+    let privkey = Scalar::from(7u64);
+    let X = VerificationKey::from_secret(&privkey);
+
+    // Prover commits, verifier challenges, prover responds.
+    let prover = IdProver::new(privkey, &mut rand::thread_rng());
+    let R = prover.commit();
+    let c = Scalar::random(&mut rand::thread_rng());
+    let s = prover.respond(c);
+    assert!(verify_id(X, R, c, s).is_ok());
+
+    assert_eq!(
+        verify_id(X, R, c + Scalar::one(), s),
+        Err(StarsigError::InvalidSignature)
+    );
+    let other = VerificationKey::from_secret(&Scalar::from(8u64));
+    assert!(verify_id(other, R, c, s).is_err());
+    assert_eq!(
+        verify_id(VerificationKey::IDENTITY, R, c, s),
+        Err(StarsigError::InvalidKey)
+    );
+}
+
+#[test]
+fn signature_chain() {
+    let privkeys: Vec<_> = (1..=3u64).map(Scalar::from).collect();
+    let mut entries: Vec<(VerificationKey, Signature)> = Vec::new();
+    for x in &privkeys {
+        let message = match entries.last() {
+            Some((_, previous)) => previous.to_bytes().to_vec(),
+            None => b"genesis".to_vec(),
+        };
+        let sig = Signature::sign_simple(b"audit log", &message, *x).unwrap();
+        entries.push((VerificationKey::from_secret(x), sig));
+    }
+    assert!(Signature::verify_chain(&entries, b"genesis", b"audit log").is_ok());
+    assert_eq!(
+        Signature::verify_chain(&entries, b"other genesis", b"audit log"),
+        Err((0, StarsigError::InvalidSignature))
+    );
+
+    // Replacing the middle link breaks the chain at that link.
+    let mut tampered = entries.clone();
+    tampered[1].1 = Signature::sign_simple(b"audit log", b"forged", privkeys[1]).unwrap();
+    assert_eq!(
+        Signature::verify_chain(&tampered, b"genesis", b"audit log"),
+        Err((1, StarsigError::InvalidSignature))
+    );
+}
+
+#[test]
+fn group_signature_over_ristretto() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let X_point = X.as_point().decompress().unwrap();
+
+    // Signatures over the Ristretto group are interchangeable with `Signature`.
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let group_sig = GroupSignature::<Ristretto>::from_signature(&sig).unwrap();
+    assert!(group_sig
+        .verify(&mut Transcript::new(b"example transcript"), X_point)
+        .is_ok());
+
+    let group_sig =
+        GroupSignature::<Ristretto>::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let sig = Signature::from(&group_sig);
+    assert!(sig
+        .verify(&mut Transcript::new(b"example transcript"), X)
+        .is_ok());
+
+    // The identity key is rejected, like by `Signature::verify`.
+    let zero = Scalar::zero();
+    let group_sig =
+        GroupSignature::<Ristretto>::sign(&mut Transcript::new(b"example transcript"), zero);
+    assert!(Ristretto::is_identity(&Ristretto::mul(
+        &zero,
+        &Ristretto::basepoint()
+    )));
+    assert_eq!(
+        group_sig.verify(
+            &mut Transcript::new(b"example transcript"),
+            Ristretto::mul(&zero, &Ristretto::basepoint())
+        ),
+        Err(StarsigError::InvalidKey)
+    );
+    assert_eq!(
+        Signature::from(&group_sig).verify(
+            &mut Transcript::new(b"example transcript"),
+            VerificationKey::IDENTITY
+        ),
+        Err(StarsigError::InvalidKey)
+    );
+}
+
+#[test]
+fn group_signature_over_mock_group() {
+    use std::ops::{Add, Mul};
+
+    // Additive group of integers modulo a prime, with insecure but valid Schnorr arithmetic.
+    const Q: u64 = 2_147_483_647;
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct Zq(u64);
+    impl Add for Zq {
+        type Output = Zq;
+        fn add(self, other: Zq) -> Zq {
+            Zq((self.0 + other.0) % Q)
+        }
+    }
+    impl Mul for Zq {
+        type Output = Zq;
+        fn mul(self, other: Zq) -> Zq {
+            Zq(self.0 * other.0 % Q)
+        }
+    }
+    struct MockGroup;
+    impl Group for MockGroup {
+        type Scalar = Zq;
+        type Point = Zq;
+        fn basepoint() -> Zq {
+            Zq(5)
+        }
+        fn mul(scalar: &Zq, point: &Zq) -> Zq {
+            *scalar * *point
+        }
+        fn random_scalar<R: rand::RngCore + rand::CryptoRng>(rng: &mut R) -> Zq {
+            Zq(rng.next_u64() % Q)
+        }
+        fn scalar_bytes(scalar: &Zq) -> Vec<u8> {
+            scalar.0.to_le_bytes().to_vec()
+        }
+        fn append_point(transcript: &mut Transcript, label: &'static [u8], point: &Zq) {
+            transcript.append_u64(label, point.0);
+        }
+        fn challenge_scalar(transcript: &mut Transcript, label: &'static [u8]) -> Zq {
+            let mut buf = [0u8; 8];
+            transcript.challenge_bytes(label, &mut buf);
+            Zq(u64::from_le_bytes(buf) % Q)
+        }
+    }
+
+    let privkey = Zq(12345);
+    let X = MockGroup::mul(&privkey, &MockGroup::basepoint());
+    let sig = GroupSignature::<MockGroup>::sign(&mut Transcript::new(b"mock"), privkey);
+    assert!(sig.verify(&mut Transcript::new(b"mock"), X).is_ok());
+    assert_eq!(
+        sig.verify(&mut Transcript::new(b"other"), X),
+        Err(StarsigError::InvalidSignature)
+    );
+
+    // The generic protocol rejects the identity key too.
+    let sig = GroupSignature::<MockGroup>::sign(&mut Transcript::new(b"mock"), Zq(0));
+    assert_eq!(
+        sig.verify(&mut Transcript::new(b"mock"), Zq(0)),
+        Err(StarsigError::InvalidKey)
+    );
+}
+
+#[test]
+fn nonce_ledger_rejects_replays() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    let mut ledger = NonceLedger::new();
+
+    // An invalid signature does not use up the nonce.
+    assert_eq!(
+        ledger.verify_once(&sig, &mut Transcript::new(b"other transcript"), X),
+        Err(StarsigError::InvalidSignature)
+    );
+    assert!(ledger.is_empty());
+
+    assert!(ledger
+        .verify_once(&sig, &mut Transcript::new(b"example transcript"), X)
+        .is_ok());
+    assert_eq!(
+        ledger.verify_once(&sig, &mut Transcript::new(b"example transcript"), X),
+        Err(StarsigError::NonceReplayed)
+    );
+    assert_eq!(ledger.len(), 1);
+
+    let other = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    assert!(ledger
+        .verify_once(&other, &mut Transcript::new(b"example transcript"), X)
+        .is_ok());
+}
+
+#[test]
+fn nonce_as_key() {
+    let sig = Signature::sign(
+        &mut Transcript::new(b"example transcript"),
+        Scalar::from(1u64),
+    );
+    let R = sig.nonce_as_key();
+    assert_eq!(R.to_bytes(), sig.R.to_bytes());
+    assert_eq!(R.as_point(), &sig.R);
+}
+
+#[test]
+fn verify_over_handshake_transcript() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let handshake = |client_nonce: &[u8]| {
+        let mut t = Transcript::new(b"handshake");
+        t.append_message(b"client-hello", b"version 1");
+        t.append_message(b"client-nonce", client_nonce);
+        t.append_u64(b"cipher-suite", 3);
+        t.append_message(b"server-hello", b"version 1");
+        t
+    };
+
+    let sig = Signature::sign(&mut handshake(b"nonce a"), privkey);
+    assert!(sig.verify(&mut handshake(b"nonce a"), X).is_ok());
+    // The signature covers every prior message, not only the last one.
+    assert!(sig.verify(&mut handshake(b"nonce b"), X).is_err());
+}
+
+#[test]
+fn verification_key_conditional_select() {
+    use subtle::{Choice, ConditionallySelectable};
+
+    let a = VerificationKey::from_secret(&Scalar::from(1u64));
+    let b = VerificationKey::from_secret(&Scalar::from(2u64));
+    assert_eq!(
+        VerificationKey::conditional_select(&a, &b, Choice::from(0)).to_bytes(),
+        a.to_bytes()
+    );
+    assert_eq!(
+        VerificationKey::conditional_select(&a, &b, Choice::from(1)).to_bytes(),
+        b.to_bytes()
+    );
+
+    let mut c = a;
+    c.conditional_assign(&b, Choice::from(1));
+    assert_eq!(c, b);
+}
+
+#[cfg(feature = "tracing")]
+#[tracing_test::traced_test]
+#[test]
+fn tracing_span_around_verify() {
+    let privkey = Scalar::from(1u64);
+    let X = VerificationKey::from_secret(&privkey);
+    let sig = Signature::sign(&mut Transcript::new(b"example transcript"), privkey);
+    assert!(sig
+        .verify(&mut Transcript::new(b"example transcript"), X)
+        .is_ok());
+
+    assert!(logs_contain("starsig.verify"));
+    assert!(logs_contain(&format!("key={}", hex::encode(X.as_bytes()))));
+    assert!(logs_contain("verified: true"));
+}
+
 #[test]
 fn empty_batch() {
     let batch = BatchVerifier::new(rand::thread_rng());
@@ -100,7 +1824,7 @@ fn sign_and_verify_batch() {
     );
     sig2.verify_batched(
         &mut Transcript::new(b"example transcript 2"),
-        pub1.clone(),
+        pub1,
         &mut bad_batch,
     );
     sig3.verify_batched(
@@ -2,11 +2,75 @@ use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 
+/// Label of the domain separator committed before the signature challenge.
+pub const LABEL_DOMAIN_SEP: &[u8] = b"dom-sep";
+/// Domain separator of the single-message signature protocol.
+pub const DOMAIN_SEP: &[u8] = b"starsig v1";
+/// Label of the verification key `X` committed to the transcript.
+pub const LABEL_KEY: &[u8] = b"X";
+/// Label of the nonce commitment `R` committed to the transcript.
+pub const LABEL_NONCE: &[u8] = b"R";
+/// Label of the challenge scalar `c`.
+pub const LABEL_CHALLENGE: &[u8] = b"c";
+
+/// Order in which the verification key `X` and the nonce commitment `R`
+/// are committed to the transcript (after the message `m`) to compute the challenge.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ChallengeLayout {
+    /// `c = H(X, R, m)`, the default layout.
+    #[default]
+    KeyFirst,
+    /// `c = H(R, X, m)`, for interoperability with implementations that commit the nonce first.
+    NonceFirst,
+}
+
+impl ChallengeLayout {
+    /// Makes the challenge for the key `X` and the nonce commitment `R`.
+    /// The message has already been fed into the transcript.
+    pub(crate) fn challenge(
+        self,
+        transcript: &mut impl TranscriptProtocol,
+        X: &CompressedRistretto,
+        R: &CompressedRistretto,
+    ) -> Scalar {
+        transcript.starsig_domain_sep();
+        match self {
+            ChallengeLayout::KeyFirst => {
+                transcript.append_point(LABEL_KEY, X);
+                transcript.append_point(LABEL_NONCE, R);
+            }
+            ChallengeLayout::NonceFirst => {
+                transcript.append_point(LABEL_NONCE, R);
+                transcript.append_point(LABEL_KEY, X);
+            }
+        }
+        transcript.challenge_scalar(LABEL_CHALLENGE)
+    }
+}
+
 /// Extension trait to the Merlin transcript API that allows committing scalars and points and
 /// generating challenges as scalars.
+///
+/// Signature verification is generic over this trait, so an alternative Fiat-Shamir backend
+/// (e.g. a deterministic stub in unit tests) can be used in place of the Merlin transcript.
 pub trait TranscriptProtocol {
     /// Commit a domain separator for a single-message signature protocol.
     fn starsig_domain_sep(&mut self);
+    /// Commit a variable-length `field` with the given `label`.
+    /// The field is framed with its label and length, so consecutive fields cannot be confused
+    /// by moving bytes from one to the other (e.g. `("ab", "c")` and `("a", "bc")`).
+    ///
+    /// By default, the length is committed as a scalar, followed by the field
+    /// in zero-padded 32-byte chunks committed as points, all with the same `label`.
+    /// The Merlin transcript commits the field as a single message instead.
+    fn commit_field(&mut self, label: &'static [u8], field: &[u8]) {
+        self.append_scalar(label, &Scalar::from(field.len() as u64));
+        for chunk in field.chunks(32) {
+            let mut buf = [0u8; 32];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            self.append_point(label, &CompressedRistretto(buf));
+        }
+    }
     /// Commit a `scalar` with the given `label`.
     fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar);
     /// Commit a `point` with the given `label`.
@@ -17,7 +81,12 @@ pub trait TranscriptProtocol {
 
 impl TranscriptProtocol for Transcript {
     fn starsig_domain_sep(&mut self) {
-        self.append_message(b"dom-sep", b"starsig v1");
+        self.append_message(LABEL_DOMAIN_SEP, DOMAIN_SEP);
+    }
+
+    fn commit_field(&mut self, label: &'static [u8], field: &[u8]) {
+        // Merlin prefixes each message with its label and length.
+        self.append_message(label, field);
     }
 
     fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar) {
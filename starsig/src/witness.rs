@@ -0,0 +1,31 @@
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+
+/// Values of a verified signature that make up its verification equation `s·G == R + c·X`,
+/// for a downstream verifier (e.g. a circuit) that does not re-run the transcript.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VerifyWitness {
+    /// Challenge `c` computed from the transcript
+    pub c: Scalar,
+    /// Signature scalar `s`
+    pub s: Scalar,
+    /// Nonce commitment `R`
+    pub R: CompressedRistretto,
+    /// Verification key `X`
+    pub X: CompressedRistretto,
+}
+
+impl VerifyWitness {
+    /// Size of the encoded witness in bytes.
+    pub const SIZE: usize = 128;
+
+    /// Encodes the witness as `c || s || R || X`.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut buf = [0u8; Self::SIZE];
+        buf[..32].copy_from_slice(self.c.as_bytes());
+        buf[32..64].copy_from_slice(self.s.as_bytes());
+        buf[64..96].copy_from_slice(self.R.as_bytes());
+        buf[96..].copy_from_slice(self.X.as_bytes());
+        buf
+    }
+}